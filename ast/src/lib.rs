@@ -1,156 +1,452 @@
-use lexer::Token;
-use std::any::Any;
+use lexer::{Span, Token};
+use std::fmt;
+use std::sync::Arc;
+
+// Every node can be printed (Display), reports the literal of the token it starts with
+// and knows the source span it was parsed from
+pub trait Node: fmt::Display {
+    fn token_literal(&self) -> &str;
+    fn span(&self) -> Span;
+}
+
+// Implements Node for structs with a `token` (their first token) and a `span` field
+macro_rules! impl_node {
+    ($($node:ty),* $(,)?) => {
+        $(
+            impl Node for $node {
+                fn token_literal(&self) -> &str {
+                    &self.token.literal
+                }
+
+                fn span(&self) -> Span {
+                    self.span
+                }
+            }
+        )*
+    };
+}
+
+impl_node!(
+    Identifier,
+    LetStatement,
+    ReturnStatement,
+    ExpressionStatement,
+    BlockStatement,
+    IntegerLiteral,
+    StringLiteral,
+    Boolean,
+    PrefixExpression,
+    InfixExpression,
+    IfExpression,
+    FunctionLiteral,
+    CallExpression,
+    ArrayLiteral,
+    IndexExpression,
+    HashLiteral,
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Let(LetStatement),
+    Return(ReturnStatement),
+    Expression(ExpressionStatement),
+}
+
+impl Node for Statement {
+    fn token_literal(&self) -> &str {
+        match self {
+            Statement::Let(statement) => statement.token_literal(),
+            Statement::Return(statement) => statement.token_literal(),
+            Statement::Expression(statement) => statement.token_literal(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Statement::Let(statement) => statement.span,
+            Statement::Return(statement) => statement.span,
+            Statement::Expression(statement) => statement.span,
+        }
+    }
+}
 
-pub trait Node {
-    fn token_literal(&self) -> String;
-    fn as_string(&self) -> String;
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Statement::Let(statement) => statement.fmt(f),
+            Statement::Return(statement) => statement.fmt(f),
+            Statement::Expression(statement) => statement.fmt(f),
+        }
+    }
 }
 
-pub trait Statement: Node + Any {
-    fn statement_node(&self);
-    fn as_any(&self) -> &dyn Any;
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Identifier(Identifier),
+    IntegerLiteral(IntegerLiteral),
+    StringLiteral(StringLiteral),
+    Boolean(Boolean),
+    Prefix(PrefixExpression),
+    Infix(InfixExpression),
+    If(Box<IfExpression>),
+    // Shared, so function values made from it at runtime need not copy the body
+    FunctionLiteral(Arc<FunctionLiteral>),
+    Call(CallExpression),
+    ArrayLiteral(ArrayLiteral),
+    Index(IndexExpression),
+    HashLiteral(HashLiteral),
 }
 
-// Modified Expression trait
-pub trait Expression: Node + Any {
-    // Added Any
-    fn expression_node(&self);
-    fn as_any(&self) -> &dyn Any; // Added this method
+impl Node for Expression {
+    fn token_literal(&self) -> &str {
+        match self {
+            Expression::Identifier(expression) => expression.token_literal(),
+            Expression::IntegerLiteral(expression) => expression.token_literal(),
+            Expression::StringLiteral(expression) => expression.token_literal(),
+            Expression::Boolean(expression) => expression.token_literal(),
+            Expression::Prefix(expression) => expression.token_literal(),
+            Expression::Infix(expression) => expression.token_literal(),
+            Expression::If(expression) => expression.token_literal(),
+            Expression::FunctionLiteral(expression) => expression.token_literal(),
+            Expression::Call(expression) => expression.token_literal(),
+            Expression::ArrayLiteral(expression) => expression.token_literal(),
+            Expression::Index(expression) => expression.token_literal(),
+            Expression::HashLiteral(expression) => expression.token_literal(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Expression::Identifier(expression) => expression.span,
+            Expression::IntegerLiteral(expression) => expression.span,
+            Expression::StringLiteral(expression) => expression.span,
+            Expression::Boolean(expression) => expression.span,
+            Expression::Prefix(expression) => expression.span,
+            Expression::Infix(expression) => expression.span,
+            Expression::If(expression) => expression.span,
+            Expression::FunctionLiteral(expression) => expression.span,
+            Expression::Call(expression) => expression.span,
+            Expression::ArrayLiteral(expression) => expression.span,
+            Expression::Index(expression) => expression.span,
+            Expression::HashLiteral(expression) => expression.span,
+        }
+    }
 }
 
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Identifier(expression) => expression.fmt(f),
+            Expression::IntegerLiteral(expression) => expression.fmt(f),
+            Expression::StringLiteral(expression) => expression.fmt(f),
+            Expression::Boolean(expression) => expression.fmt(f),
+            Expression::Prefix(expression) => expression.fmt(f),
+            Expression::Infix(expression) => expression.fmt(f),
+            Expression::If(expression) => expression.fmt(f),
+            Expression::FunctionLiteral(expression) => expression.fmt(f),
+            Expression::Call(expression) => expression.fmt(f),
+            Expression::ArrayLiteral(expression) => expression.fmt(f),
+            Expression::Index(expression) => expression.fmt(f),
+            Expression::HashLiteral(expression) => expression.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Program {
-    pub statements: Vec<Box<dyn Statement>>,
+    pub statements: Vec<Statement>,
 }
 
 impl Node for Program {
-    fn token_literal(&self) -> String {
-        if let Some(first) = self.statements.first() {
-            first.token_literal()
-        } else {
-            String::new()
+    fn token_literal(&self) -> &str {
+        self.statements
+            .first()
+            .map_or("", |statement| statement.token_literal())
+    }
+
+    fn span(&self) -> Span {
+        match (self.statements.first(), self.statements.last()) {
+            (Some(first), Some(last)) => first.span().to(last.span()),
+            _ => Span::default(),
         }
     }
+}
 
-    fn as_string(&self) -> String {
-        self.statements
-            .iter()
-            .map(|statement| statement.as_string())
-            .collect::<Vec<String>>()
-            .join("")
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for statement in &self.statements {
+            write!(f, "{}", statement)?;
+        }
+        Ok(())
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Identifier {
     pub token: Token,
+    pub span: Span,
     pub value: String,
 }
 
-// Modified Identifier impl for Expression
-impl Expression for Identifier {
-    fn expression_node(&self) {}
-    fn as_any(&self) -> &dyn Any {
-        // Implemented as_any
-        self
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetStatement {
+    pub token: Token,
+    pub span: Span,
+    pub name: Identifier,
+    pub value: Expression,
+}
+
+impl fmt::Display for LetStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "let {} = {};", self.name, self.value)
     }
 }
 
-impl Node for Identifier {
-    fn token_literal(&self) -> String {
-        self.token.literal.clone()
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnStatement {
+    pub token: Token,
+    pub span: Span,
+    pub return_value: Option<Expression>,
+}
+
+impl fmt::Display for ReturnStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.return_value {
+            Some(return_value) => write!(f, "return {};", return_value),
+            None => write!(f, "return;"),
+        }
     }
+}
 
-    fn as_string(&self) -> String {
-        self.value.clone()
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionStatement {
+    pub token: Token, // The first token of the expression
+    pub span: Span,
+    pub expression: Expression,
+}
+
+impl fmt::Display for ExpressionStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.expression.fmt(f)
     }
 }
 
-#[allow(dead_code)]
-pub struct LetStatement {
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegerLiteral {
+    pub token: Token,
+    pub span: Span,
+    pub value: i64,
+}
+
+impl fmt::Display for IntegerLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.token.literal)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringLiteral {
     pub token: Token,
-    pub name: Option<Box<Identifier>>,
-    pub value: Option<Box<dyn Expression>>,
+    pub span: Span,
+    pub value: String, // Contents with escape sequences already resolved
 }
 
-impl Statement for LetStatement {
-    fn statement_node(&self) {}
+// Prints the literal back in source form, re-escaping what the lexer unescaped
+impl fmt::Display for StringLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for ch in self.value.chars() {
+            match ch {
+                '\n' => write!(f, "\\n")?,
+                '\t' => write!(f, "\\t")?,
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                ch if ch.is_control() => write!(f, "\\u{{{:x}}}", ch as u32)?,
+                ch => write!(f, "{}", ch)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boolean {
+    pub token: Token,
+    pub span: Span,
+    pub value: bool,
+}
 
-    fn as_any(&self) -> &dyn Any {
-        self
+impl fmt::Display for Boolean {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.token.literal)
     }
 }
 
-impl Node for LetStatement {
-    fn token_literal(&self) -> String {
-        self.token.literal.clone()
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixExpression {
+    pub token: Token, // The prefix token, e.g. ! or -
+    pub span: Span,
+    pub operator: String,
+    pub right: Box<Expression>,
+}
+
+impl fmt::Display for PrefixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}{})", self.operator, self.right)
     }
+}
 
-    fn as_string(&self) -> String {
-        let mut output = String::new();
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfixExpression {
+    pub token: Token, // The operator token, e.g. +
+    pub span: Span,
+    pub left: Box<Expression>,
+    pub operator: String,
+    pub right: Box<Expression>,
+}
 
-        if let Some(name) = &self.name {
-            output.push_str("let ");
-            output.push_str(&name.as_string());
-            output.push_str(" = ");
+// Fully parenthesized so the grouping chosen by the parser is visible
+impl fmt::Display for InfixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.operator, self.right)
+    }
+}
 
-            if let Some(value) = &self.value {
-                output.push_str(&value.as_string());
-            }
-        } else {
-            output.push_str("let _ = _");
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockStatement {
+    pub token: Token, // The { token
+    pub span: Span,
+    pub statements: Vec<Statement>,
+}
+
+impl fmt::Display for BlockStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.statements.is_empty() {
+            return write!(f, "{{}}");
         }
 
-        output.push(';');
-        output
+        write!(f, "{{ ")?;
+        for statement in &self.statements {
+            write!(f, "{}", statement)?;
+        }
+        write!(f, " }}")
     }
 }
 
-pub struct ReturnStatement {
-    pub token: Token,
-    pub return_value: Option<Box<dyn Expression>>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfExpression {
+    pub token: Token, // The 'if' token
+    pub span: Span,
+    pub condition: Box<Expression>,
+    pub consequence: BlockStatement,
+    pub alternative: Option<BlockStatement>,
 }
 
-impl Node for ReturnStatement {
-    fn token_literal(&self) -> String {
-        self.token.literal.clone()
+impl fmt::Display for IfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "if {} {}", self.condition, self.consequence)?;
+        if let Some(alternative) = &self.alternative {
+            write!(f, " else {}", alternative)?;
+        }
+        Ok(())
     }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionLiteral {
+    pub token: Token, // The 'fn' token
+    pub span: Span,
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+}
 
-    fn as_string(&self) -> String {
-        "return ...;".to_string() // Placeholder, actual implementation might differ
+impl fmt::Display for FunctionLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|parameter| parameter.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}({}) {}", self.token.literal, parameters, self.body)
     }
 }
 
-impl Statement for ReturnStatement {
-    fn statement_node(&self) {}
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallExpression {
+    pub token: Token, // The '(' token
+    pub span: Span,
+    pub function: Box<Expression>, // Identifier or FunctionLiteral
+    pub arguments: Vec<Expression>,
+}
 
-    fn as_any(&self) -> &dyn Any {
-        self
+impl fmt::Display for CallExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}({})", self.function, arguments)
     }
 }
 
-// ExpressionStatement struct (ensure pub fields if accessed directly in parser tests)
-pub struct ExpressionStatement {
-    pub token: Token, // The first token of the expression
-    pub expression: Option<Box<dyn Expression>>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayLiteral {
+    pub token: Token, // The '[' token
+    pub span: Span,
+    pub elements: Vec<Expression>,
 }
 
-impl Statement for ExpressionStatement {
-    fn statement_node(&self) {}
-    fn as_any(&self) -> &dyn Any {
-        self
+impl fmt::Display for ArrayLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| element.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "[{}]", elements)
     }
 }
 
-impl Node for ExpressionStatement {
-    fn token_literal(&self) -> String {
-        self.token.literal.clone()
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexExpression {
+    pub token: Token, // The '[' token
+    pub span: Span,
+    pub left: Box<Expression>,
+    pub index: Box<Expression>,
+}
+
+impl fmt::Display for IndexExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}[{}])", self.left, self.index)
     }
+}
 
-    fn as_string(&self) -> String {
-        if let Some(expression) = &self.expression {
-            expression.as_string()
-        } else {
-            String::new()
-        }
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashLiteral {
+    pub token: Token, // The '{' token
+    pub span: Span,
+    pub pairs: Vec<(Expression, Expression)>, // In source order
+}
+
+impl fmt::Display for HashLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs = self
+            .pairs
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{{{}}}", pairs)
     }
 }
 
@@ -166,7 +462,9 @@ mod tests {
             token: Token {
                 token_type: TokenType::Identifier,
                 literal: "myVar".to_string(),
+                span: Span::new(4, 9, 1, 5),
             },
+            span: Span::new(4, 9, 1, 5),
             value: "myVar".to_string(),
         };
 
@@ -175,7 +473,9 @@ mod tests {
             token: Token {
                 token_type: TokenType::Identifier,
                 literal: "anotherVar".to_string(),
+                span: Span::new(12, 22, 1, 13),
             },
+            span: Span::new(12, 22, 1, 13),
             value: "anotherVar".to_string(),
         };
 
@@ -184,17 +484,47 @@ mod tests {
             token: Token {
                 token_type: TokenType::Let,
                 literal: "let".to_string(),
+                span: Span::new(0, 3, 1, 1),
             },
-            name: Some(Box::new(name)),
-            value: Some(Box::new(value)),
+            span: Span::new(0, 23, 1, 1),
+            name,
+            value: Expression::Identifier(value),
         };
 
         // Create a program with the let statement
         let program = Program {
-            statements: vec![Box::new(let_stmt)],
+            statements: vec![Statement::Let(let_stmt)],
         };
 
         // Test the string representation
-        assert_eq!(program.as_string(), "let myVar = anotherVar;");
+        assert_eq!(program.to_string(), "let myVar = anotherVar;");
+        assert_eq!(program.token_literal(), "let");
+        assert_eq!(program.span(), Span::new(0, 23, 1, 1));
+    }
+
+    #[test]
+    fn test_clone_and_compare() {
+        let program = Program {
+            statements: vec![Statement::Return(ReturnStatement {
+                token: Token::new(TokenType::Return, "return".to_string()),
+                span: Span::default(),
+                return_value: Some(Expression::Boolean(Boolean {
+                    token: Token::new(TokenType::True, "true".to_string()),
+                    span: Span::default(),
+                    value: true,
+                })),
+            })],
+        };
+
+        let copy = program.clone();
+        assert_eq!(copy, program);
+
+        match &copy.statements[0] {
+            Statement::Return(ReturnStatement {
+                return_value: Some(Expression::Boolean(boolean)),
+                ..
+            }) => assert!(boolean.value),
+            other => panic!("expected return of a boolean, got {:?}", other),
+        }
     }
 }
@@ -0,0 +1,139 @@
+use crate::error::RuntimeError;
+use crate::object::Object;
+use lexer::Span;
+use std::fmt;
+
+type BuiltinFn = fn(&[Object], Span) -> Result<Object, RuntimeError>;
+
+// A function provided by the interpreter rather than written in Mazgamet.
+// Builtins are looked up by name after the environment, so a `let` can shadow them.
+#[derive(Debug, Clone, Copy)]
+pub struct Builtin {
+    pub name: &'static str,
+    function: BuiltinFn,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "len",
+        function: len,
+    },
+    Builtin {
+        name: "first",
+        function: first,
+    },
+    Builtin {
+        name: "last",
+        function: last,
+    },
+    Builtin {
+        name: "rest",
+        function: rest,
+    },
+    Builtin {
+        name: "push",
+        function: push,
+    },
+];
+
+impl Builtin {
+    pub fn lookup(name: &str) -> Option<Builtin> {
+        BUILTINS
+            .iter()
+            .find(|builtin| builtin.name == name)
+            .copied()
+    }
+
+    // `span` is the span of the call, used for any error the builtin reports
+    pub fn call(&self, arguments: &[Object], span: Span) -> Result<Object, RuntimeError> {
+        (self.function)(arguments, span)
+    }
+}
+
+// Builtins are unique by name, which avoids comparing function pointers
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "builtin function {}", self.name)
+    }
+}
+
+fn expect_arguments(arguments: &[Object], expected: usize, span: Span) -> Result<(), RuntimeError> {
+    if arguments.len() != expected {
+        return Err(RuntimeError::WrongArgumentCount {
+            expected,
+            found: arguments.len(),
+            span,
+        });
+    }
+    Ok(())
+}
+
+fn unsupported(function: &'static str, argument: &Object, span: Span) -> RuntimeError {
+    RuntimeError::UnsupportedArgument {
+        function,
+        found: argument.type_name(),
+        span,
+    }
+}
+
+// The array argument of `first`, `last`, `rest` and `push`
+fn array_argument<'a>(
+    function: &'static str,
+    argument: &'a Object,
+    span: Span,
+) -> Result<&'a [Object], RuntimeError> {
+    match argument {
+        Object::Array(elements) => Ok(elements),
+        other => Err(unsupported(function, other, span)),
+    }
+}
+
+// Number of elements of an array, or of characters (not bytes) of a string
+fn len(arguments: &[Object], span: Span) -> Result<Object, RuntimeError> {
+    expect_arguments(arguments, 1, span)?;
+    let length = match &arguments[0] {
+        Object::String(value) => value.chars().count(),
+        Object::Array(elements) => elements.len(),
+        other => return Err(unsupported("len", other, span)),
+    };
+    i64::try_from(length)
+        .map(Object::Integer)
+        .map_err(|_| RuntimeError::IntegerOverflow { span })
+}
+
+fn first(arguments: &[Object], span: Span) -> Result<Object, RuntimeError> {
+    expect_arguments(arguments, 1, span)?;
+    let elements = array_argument("first", &arguments[0], span)?;
+    Ok(elements.first().cloned().unwrap_or(Object::Null))
+}
+
+fn last(arguments: &[Object], span: Span) -> Result<Object, RuntimeError> {
+    expect_arguments(arguments, 1, span)?;
+    let elements = array_argument("last", &arguments[0], span)?;
+    Ok(elements.last().cloned().unwrap_or(Object::Null))
+}
+
+// Every element but the first, as a new array; `null` for an empty array
+fn rest(arguments: &[Object], span: Span) -> Result<Object, RuntimeError> {
+    expect_arguments(arguments, 1, span)?;
+    let elements = array_argument("rest", &arguments[0], span)?;
+    match elements.split_first() {
+        Some((_, rest)) => Ok(Object::Array(rest.to_vec())),
+        None => Ok(Object::Null),
+    }
+}
+
+// A new array with the element appended; the original is left unchanged
+fn push(arguments: &[Object], span: Span) -> Result<Object, RuntimeError> {
+    expect_arguments(arguments, 2, span)?;
+    let elements = array_argument("push", &arguments[0], span)?;
+    let mut pushed = elements.to_vec();
+    pushed.push(arguments[1].clone());
+    Ok(Object::Array(pushed))
+}
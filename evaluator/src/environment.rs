@@ -0,0 +1,341 @@
+use crate::object::Object;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+// Fewest registered scopes before a collection runs while evaluating
+const MIN_COLLECTION_THRESHOLD: usize = 1024;
+
+type ScopeRef = Rc<RefCell<Scope>>;
+
+struct Scope {
+    store: HashMap<String, Object>,
+    outer: Option<Environment>,
+}
+
+// Every scope created under one top-level environment, so reference cycles among
+// them can be found and broken. Closures make such cycles easily: in
+// `let add_two = new_adder(2)` the global scope holds add_two, which holds the
+// call scope of new_adder, which encloses the global scope again.
+struct Heap {
+    scopes: RefCell<Vec<Weak<RefCell<Scope>>>>,
+    // Number of registered scopes at which the next collection runs
+    threshold: Cell<usize>,
+    // Number of root handles alive
+    roots: Cell<usize>,
+    // Once no root is left, the number of handles held outside the heap as of the
+    // last collection, less those dropped since
+    outside: Cell<usize>,
+    collecting: Cell<bool>,
+}
+
+// Bindings created by `let` and by function parameters. A function call runs in a
+// child scope enclosing the function's own environment: lookups fall back to the
+// enclosing scopes, while new bindings only ever go into the innermost one.
+//
+// An Environment is a shared handle: clones refer to the same scope, which is how
+// functions capture the scope they were defined in and see later bindings made in it.
+//
+// Every Environment handed out is a root: dropping one frees whatever scopes only
+// the bindings themselves were keeping alive, cycles included. Scopes and function
+// values refer to each other through handles that are not roots. Once no root is
+// left nothing can be evaluated, so the only handles that come and go are those of
+// function values that outlived their environment; when all of those are gone,
+// the heap is collected once more.
+pub struct Environment {
+    scope: ScopeRef,
+    heap: Rc<Heap>,
+    root: bool,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        let heap = Rc::new(Heap {
+            scopes: RefCell::new(Vec::new()),
+            threshold: Cell::new(MIN_COLLECTION_THRESHOLD),
+            roots: Cell::new(1),
+            outside: Cell::new(0),
+            collecting: Cell::new(false),
+        });
+        Self {
+            scope: heap.register(None),
+            heap,
+            root: true,
+        }
+    }
+
+    // An empty scope nested inside `outer`
+    pub fn new_enclosed(outer: &Environment) -> Self {
+        Self::enclosed(outer).into_root()
+    }
+
+    // Like new_enclosed, for the scope of a call
+    pub(crate) fn enclosed(outer: &Environment) -> Self {
+        outer.heap.maybe_collect();
+        Self {
+            scope: outer.heap.register(Some(outer.handle())),
+            heap: outer.heap.clone(),
+            root: false,
+        }
+    }
+
+    // The innermost binding of `name`
+    pub fn get(&self, name: &str) -> Option<Object> {
+        let scope = self.scope.borrow();
+        match scope.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => scope.outer.as_ref().and_then(|outer| outer.get(name)),
+        }
+    }
+
+    // Binds `name` in this scope, shadowing any binding of it in enclosing scopes
+    pub fn set(&mut self, name: impl Into<String>, value: Object) {
+        // Dropped once the scope is no longer borrowed
+        let _previous = self.scope.borrow_mut().store.insert(name.into(), value);
+    }
+
+    // A handle to the same scope that is not a root, for scopes and functions to
+    // hold on to each other with
+    pub(crate) fn handle(&self) -> Environment {
+        Environment {
+            scope: self.scope.clone(),
+            heap: self.heap.clone(),
+            root: false,
+        }
+    }
+
+    // A root handle to the same scope, for handing out
+    pub(crate) fn root(&self) -> Environment {
+        self.handle().into_root()
+    }
+
+    fn into_root(mut self) -> Environment {
+        if !self.root {
+            self.heap.roots.set(self.heap.roots.get() + 1);
+            self.root = true;
+        }
+        self
+    }
+
+    #[cfg(test)]
+    pub(crate) fn live_scopes(&self) -> usize {
+        let scopes = self.heap.scopes.borrow();
+        scopes
+            .iter()
+            .filter(|scope| scope.strong_count() > 0)
+            .count()
+    }
+
+    // Gone once every scope created under this environment's root is freed
+    #[cfg(test)]
+    pub(crate) fn downgrade_heap(&self) -> Weak<impl Sized> {
+        Rc::downgrade(&self.heap)
+    }
+}
+
+impl Heap {
+    fn register(&self, outer: Option<Environment>) -> ScopeRef {
+        let scope = Rc::new(RefCell::new(Scope {
+            store: HashMap::new(),
+            outer,
+        }));
+        self.scopes.borrow_mut().push(Rc::downgrade(&scope));
+        scope
+    }
+
+    // Collects once the number of registered scopes doubles, so the work stays
+    // proportional to the number of scopes created
+    fn maybe_collect(&self) {
+        if self.scopes.borrow().len() >= self.threshold.get() {
+            self.collect(None);
+        }
+    }
+
+    // Frees the scopes that are only referenced from other scopes of this heap and
+    // not from anywhere else: a handle, a value held by the evaluator, or a scope
+    // of another heap. Those references only show up in a scope's reference count,
+    // so any scope whose count exceeds the references found inside the heap is
+    // reachable, along with everything it refers to. `dropping` is a handle about
+    // to go away and not counted.
+    fn collect(&self, dropping: Option<&ScopeRef>) {
+        // Freeing garbage drops handles, which must not start another collection
+        if self.collecting.replace(true) {
+            return;
+        }
+        let scopes: Vec<ScopeRef> = {
+            let mut registered = self.scopes.borrow_mut();
+            registered.retain(|scope| scope.strong_count() > 0);
+            registered.iter().filter_map(Weak::upgrade).collect()
+        };
+        let index: HashMap<*const RefCell<Scope>, usize> = scopes
+            .iter()
+            .enumerate()
+            .map(|(i, scope)| (Rc::as_ptr(scope), i))
+            .collect();
+
+        // Start from the full count, less our own reference from `scopes` and the
+        // handle being dropped, then take away the references from within the heap
+        let mut external: Vec<usize> = scopes
+            .iter()
+            .map(|scope| {
+                let dropped = dropping.is_some_and(|dropping| Rc::ptr_eq(dropping, scope));
+                Rc::strong_count(scope) - 1 - usize::from(dropped)
+            })
+            .collect();
+        for scope in &scopes {
+            scope.borrow().for_each_reference(&mut |env| {
+                if let Some(&i) = index.get(&Rc::as_ptr(&env.scope)) {
+                    external[i] -= 1;
+                }
+            });
+        }
+
+        let mut reachable = vec![false; scopes.len()];
+        let mut pending: Vec<usize> = (0..scopes.len()).filter(|&i| external[i] > 0).collect();
+        while let Some(i) = pending.pop() {
+            if std::mem::replace(&mut reachable[i], true) {
+                continue;
+            }
+            scopes[i].borrow().for_each_reference(&mut |env| {
+                if let Some(&i) = index.get(&Rc::as_ptr(&env.scope)) {
+                    if !reachable[i] {
+                        pending.push(i);
+                    }
+                }
+            });
+        }
+
+        // Emptying the unreachable scopes breaks their cycles. What they held is
+        // dropped only after all of them are emptied, outside of any borrow.
+        let mut garbage = Vec::new();
+        for (scope, reachable) in scopes.iter().zip(&reachable) {
+            if !reachable {
+                let mut scope = scope.borrow_mut();
+                garbage.push((std::mem::take(&mut scope.store), scope.outer.take()));
+            }
+        }
+        let live = scopes.len() - garbage.len();
+        self.threshold.set((live * 2).max(MIN_COLLECTION_THRESHOLD));
+        self.outside.set(external.iter().sum());
+        drop(garbage);
+        self.collecting.set(false);
+    }
+}
+
+impl Scope {
+    // Calls `f` with every environment this scope refers to: the enclosing one and
+    // those captured by the functions bound in it, however deeply nested in arrays
+    // and hashes
+    fn for_each_reference(&self, f: &mut impl FnMut(&Environment)) {
+        if let Some(outer) = &self.outer {
+            f(outer);
+        }
+        for value in self.store.values() {
+            for_each_captured(value, f);
+        }
+    }
+}
+
+fn for_each_captured(object: &Object, f: &mut impl FnMut(&Environment)) {
+    match object {
+        Object::Function(function) => f(function.captured()),
+        Object::Array(elements) => {
+            for element in elements {
+                for_each_captured(element, f);
+            }
+        }
+        Object::Hash(pairs) => {
+            for value in pairs.values() {
+                for_each_captured(value, f);
+            }
+        }
+        Object::ReturnValue(value) => for_each_captured(value, f),
+        _ => {}
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Clones of a root are roots too
+impl Clone for Environment {
+    fn clone(&self) -> Self {
+        if self.root {
+            self.heap.roots.set(self.heap.roots.get() + 1);
+        } else if self.heap.roots.get() == 0 {
+            // A function value outside the heap being copied
+            self.heap.outside.set(self.heap.outside.get() + 1);
+        }
+        Self {
+            scope: self.scope.clone(),
+            heap: self.heap.clone(),
+            root: self.root,
+        }
+    }
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        if self.root {
+            self.heap.roots.set(self.heap.roots.get() - 1);
+            self.heap.collect(Some(&self.scope));
+        } else if self.heap.roots.get() == 0 && !self.heap.collecting.get() {
+            // Handles freed along with a scope also count, so this may collect
+            // early, but never later than when the last outside handle goes
+            match self.heap.outside.get() {
+                0 | 1 => self.heap.collect(Some(&self.scope)),
+                outside => self.heap.outside.set(outside - 1),
+            }
+        }
+    }
+}
+
+// Two environments are equal when they are the same scope
+impl PartialEq for Environment {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.scope, &other.scope)
+    }
+}
+
+// Scopes can refer to each other through the functions bound in them, so only the
+// names bound directly in this one are shown
+impl fmt::Debug for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = self.scope.borrow();
+        let mut names: Vec<&String> = scope.store.keys().collect();
+        names.sort();
+        f.debug_struct("Environment")
+            .field("names", &names)
+            .field("enclosed", &scope.outer.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enclosed_scopes() {
+        let mut global = Environment::new();
+        global.set("x", Object::Integer(1));
+        global.set("y", Object::Integer(2));
+
+        let mut local = Environment::new_enclosed(&global);
+        local.set("x", Object::Integer(10));
+
+        assert_eq!(local.get("x"), Some(Object::Integer(10)));
+        assert_eq!(local.get("y"), Some(Object::Integer(2)));
+        assert_eq!(local.get("z"), None);
+        // Shadowing in the child leaves the parent untouched
+        assert_eq!(global.get("x"), Some(Object::Integer(1)));
+
+        // Later bindings in the parent are visible through the child
+        global.set("z", Object::Integer(3));
+        assert_eq!(local.get("z"), Some(Object::Integer(3)));
+    }
+}
@@ -0,0 +1,160 @@
+use lexer::Span;
+use std::error::Error;
+use std::fmt;
+
+// Problems that stop a program while it runs. Each carries the span of the
+// expression that failed, so it can be shown against the source like a ParseError.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeError {
+    // An identifier with no binding in scope
+    UnknownIdentifier {
+        name: String,
+        span: Span,
+    },
+    // A prefix operator applied to a type it does not support, e.g. `-true`
+    UnknownPrefixOperator {
+        operator: String,
+        right: &'static str,
+        span: Span,
+    },
+    // An infix operator applied to types it does not support, e.g. `true + false`
+    UnknownInfixOperator {
+        left: &'static str,
+        operator: String,
+        right: &'static str,
+        span: Span,
+    },
+    // An infix operator with operands of different types, e.g. `1 + true`
+    TypeMismatch {
+        left: &'static str,
+        operator: String,
+        right: &'static str,
+        span: Span,
+    },
+    DivisionByZero {
+        span: Span,
+    },
+    // Integer arithmetic whose result does not fit in an i64
+    IntegerOverflow {
+        span: Span,
+    },
+    // A call whose callee is not a function, e.g. `5(1)`
+    NotAFunction {
+        found: &'static str,
+        span: Span,
+    },
+    // A call with more or fewer arguments than the function has parameters
+    WrongArgumentCount {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    // A call made while MAX_CALL_DEPTH calls are already in progress, usually
+    // recursion without a base case
+    RecursionLimit {
+        limit: usize,
+        span: Span,
+    },
+    // A builtin called with an argument of a type it does not accept, e.g. `len(1)`
+    UnsupportedArgument {
+        function: &'static str,
+        found: &'static str,
+        span: Span,
+    },
+    // An index expression on a value that cannot be indexed, or with an index of
+    // the wrong type, e.g. `1[0]` or `[1]["a"]`
+    UnsupportedIndex {
+        left: &'static str,
+        index: &'static str,
+        span: Span,
+    },
+    // A hash key, in a literal or a lookup, of a type that cannot be hashed, e.g.
+    // `{fn() {}: 1}`
+    UnhashableKey {
+        found: &'static str,
+        span: Span,
+    },
+    // An array index below zero or past the last element
+    IndexOutOfRange {
+        index: i64,
+        length: usize,
+        span: Span,
+    },
+}
+
+impl RuntimeError {
+    pub fn span(&self) -> Span {
+        match self {
+            RuntimeError::UnknownIdentifier { span, .. }
+            | RuntimeError::UnknownPrefixOperator { span, .. }
+            | RuntimeError::UnknownInfixOperator { span, .. }
+            | RuntimeError::TypeMismatch { span, .. }
+            | RuntimeError::DivisionByZero { span }
+            | RuntimeError::IntegerOverflow { span }
+            | RuntimeError::NotAFunction { span, .. }
+            | RuntimeError::WrongArgumentCount { span, .. }
+            | RuntimeError::RecursionLimit { span, .. }
+            | RuntimeError::UnsupportedArgument { span, .. }
+            | RuntimeError::UnsupportedIndex { span, .. }
+            | RuntimeError::UnhashableKey { span, .. }
+            | RuntimeError::IndexOutOfRange { span, .. } => *span,
+        }
+    }
+
+    // The error text without its location
+    pub fn message(&self) -> String {
+        match self {
+            RuntimeError::UnknownIdentifier { name, .. } => {
+                format!("identifier not found: {}", name)
+            }
+            RuntimeError::UnknownPrefixOperator {
+                operator, right, ..
+            } => format!("unknown operator: {}{}", operator, right),
+            RuntimeError::UnknownInfixOperator {
+                left,
+                operator,
+                right,
+                ..
+            } => format!("unknown operator: {} {} {}", left, operator, right),
+            RuntimeError::TypeMismatch {
+                left,
+                operator,
+                right,
+                ..
+            } => format!("type mismatch: {} {} {}", left, operator, right),
+            RuntimeError::DivisionByZero { .. } => "division by zero".to_string(),
+            RuntimeError::IntegerOverflow { .. } => "integer overflow".to_string(),
+            RuntimeError::NotAFunction { found, .. } => format!("not a function: {}", found),
+            RuntimeError::WrongArgumentCount {
+                expected, found, ..
+            } => format!(
+                "wrong number of arguments: expected {}, got {}",
+                expected, found
+            ),
+            RuntimeError::RecursionLimit { limit, .. } => {
+                format!("maximum call depth of {} exceeded", limit)
+            }
+            RuntimeError::UnsupportedArgument {
+                function, found, ..
+            } => format!("argument to `{}` not supported, got {}", function, found),
+            RuntimeError::UnsupportedIndex { left, index, .. } => {
+                format!("index operator not supported: {}[{}]", left, index)
+            }
+            RuntimeError::UnhashableKey { found, .. } => {
+                format!("unusable as hash key: {}", found)
+            }
+            RuntimeError::IndexOutOfRange { index, length, .. } => format!(
+                "index out of range: index {} but length is {}",
+                index, length
+            ),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span(), self.message())
+    }
+}
+
+impl Error for RuntimeError {}
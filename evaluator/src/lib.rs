@@ -0,0 +1,891 @@
+mod builtins;
+mod environment;
+mod error;
+mod object;
+
+use ast::{
+    BlockStatement, CallExpression, Expression, HashLiteral, IfExpression, IndexExpression,
+    InfixExpression, Node, PrefixExpression, Program, Statement,
+};
+use lexer::Span;
+use std::collections::HashMap;
+
+pub use builtins::Builtin;
+pub use environment::Environment;
+pub use error::RuntimeError;
+pub use object::{Function, HashKey, Object};
+
+type EvalResult = Result<Object, RuntimeError>;
+
+// Most calls that may be in progress at once. Each one takes native stack, so
+// without a limit deep recursion would overflow it and abort the process.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+// Native stack a thread running `eval` should have for MAX_CALL_DEPTH calls to
+// fit, with room to spare even in unoptimized builds. A main thread's default
+// is typically 8 MiB and other threads get 2 MiB.
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+// Runs `program` and returns the value of its last statement, or of the first
+// `return` reached. Bindings made by `let` are left in `env`, so a REPL can pass
+// the same environment for every line.
+pub fn eval(program: &Program, env: &mut Environment) -> EvalResult {
+    let mut result = Object::Null;
+    for statement in &program.statements {
+        result = eval_statement(statement, env, 0)?;
+        if let Object::ReturnValue(value) = result {
+            return Ok(*value);
+        }
+    }
+    Ok(result)
+}
+
+// Unlike a program, a block hands a `return` on still wrapped, so it keeps
+// unwinding through any blocks around it
+fn eval_block_statement(block: &BlockStatement, env: &mut Environment, depth: usize) -> EvalResult {
+    let mut result = Object::Null;
+    for statement in &block.statements {
+        result = eval_statement(statement, env, depth)?;
+        if let Object::ReturnValue(_) = result {
+            return Ok(result);
+        }
+    }
+    Ok(result)
+}
+
+fn eval_statement(statement: &Statement, env: &mut Environment, depth: usize) -> EvalResult {
+    match statement {
+        Statement::Let(statement) => {
+            let value = eval_expression(&statement.value, env, depth)?;
+            env.set(statement.name.value.clone(), value);
+            Ok(Object::Null)
+        }
+        Statement::Return(statement) => {
+            let value = match &statement.return_value {
+                Some(expression) => eval_expression(expression, env, depth)?,
+                None => Object::Null,
+            };
+            Ok(Object::ReturnValue(Box::new(value)))
+        }
+        Statement::Expression(statement) => eval_expression(&statement.expression, env, depth),
+    }
+}
+
+fn eval_expression(expression: &Expression, env: &mut Environment, depth: usize) -> EvalResult {
+    match expression {
+        Expression::Identifier(identifier) => env
+            .get(&identifier.value)
+            .or_else(|| Builtin::lookup(&identifier.value).map(Object::Builtin))
+            .ok_or_else(|| RuntimeError::UnknownIdentifier {
+                name: identifier.value.clone(),
+                span: identifier.span,
+            }),
+        Expression::IntegerLiteral(literal) => Ok(Object::Integer(literal.value)),
+        Expression::StringLiteral(literal) => Ok(Object::String(literal.value.clone())),
+        Expression::Boolean(boolean) => Ok(Object::Boolean(boolean.value)),
+        Expression::Prefix(prefix) => eval_prefix_expression(prefix, env, depth),
+        Expression::Infix(infix) => eval_infix_expression(infix, env, depth),
+        Expression::If(if_expression) => eval_if_expression(if_expression, env, depth),
+        Expression::FunctionLiteral(literal) => Ok(Object::Function(Function::new(
+            literal.clone(),
+            env.handle(),
+        ))),
+        Expression::Call(call) => eval_call_expression(call, env, depth),
+        Expression::ArrayLiteral(array) => Ok(Object::Array(eval_expressions(
+            &array.elements,
+            env,
+            depth,
+        )?)),
+        Expression::Index(index) => eval_index_expression(index, env, depth),
+        Expression::HashLiteral(hash) => eval_hash_literal(hash, env, depth),
+    }
+}
+
+fn eval_expressions(
+    expressions: &[Expression],
+    env: &mut Environment,
+    depth: usize,
+) -> Result<Vec<Object>, RuntimeError> {
+    expressions
+        .iter()
+        .map(|expression| eval_expression(expression, env, depth))
+        .collect()
+}
+
+fn eval_call_expression(call: &CallExpression, env: &mut Environment, depth: usize) -> EvalResult {
+    let function = match eval_expression(&call.function, env, depth)? {
+        Object::Function(function) => function,
+        Object::Builtin(builtin) => {
+            let arguments = eval_expressions(&call.arguments, env, depth)?;
+            return builtin.call(&arguments, call.span);
+        }
+        other => {
+            return Err(RuntimeError::NotAFunction {
+                found: other.type_name(),
+                span: call.span,
+            })
+        }
+    };
+    if call.arguments.len() != function.parameters().len() {
+        return Err(RuntimeError::WrongArgumentCount {
+            expected: function.parameters().len(),
+            found: call.arguments.len(),
+            span: call.span,
+        });
+    }
+    if depth >= MAX_CALL_DEPTH {
+        return Err(RuntimeError::RecursionLimit {
+            limit: MAX_CALL_DEPTH,
+            span: call.span,
+        });
+    }
+
+    // Parameters live in a fresh scope around the function's own environment, so
+    // the body sees the bindings from where it was defined, not from the caller
+    let mut scope = Environment::enclosed(function.captured());
+    for (parameter, argument) in function.parameters().iter().zip(&call.arguments) {
+        scope.set(
+            parameter.value.clone(),
+            eval_expression(argument, env, depth)?,
+        );
+    }
+
+    match eval_block_statement(function.body(), &mut scope, depth + 1)? {
+        Object::ReturnValue(value) => Ok(*value),
+        value => Ok(value),
+    }
+}
+
+fn eval_index_expression(
+    index: &IndexExpression,
+    env: &mut Environment,
+    depth: usize,
+) -> EvalResult {
+    let left = eval_expression(&index.left, env, depth)?;
+    let position = eval_expression(&index.index, env, depth)?;
+    match (&left, &position) {
+        (Object::Array(elements), Object::Integer(position)) => usize::try_from(*position)
+            .ok()
+            .and_then(|position| elements.get(position))
+            .cloned()
+            .ok_or(RuntimeError::IndexOutOfRange {
+                index: *position,
+                length: elements.len(),
+                span: index.span,
+            }),
+        (Object::Hash(pairs), key) => {
+            let key = HashKey::from_object(key).ok_or(RuntimeError::UnhashableKey {
+                found: key.type_name(),
+                span: index.span,
+            })?;
+            Ok(pairs.get(&key).cloned().unwrap_or(Object::Null))
+        }
+        _ => Err(RuntimeError::UnsupportedIndex {
+            left: left.type_name(),
+            index: position.type_name(),
+            span: index.span,
+        }),
+    }
+}
+
+// Pairs are evaluated in source order; a repeated key keeps the last value
+fn eval_hash_literal(hash: &HashLiteral, env: &mut Environment, depth: usize) -> EvalResult {
+    let mut pairs = HashMap::with_capacity(hash.pairs.len());
+    for (key_expression, value_expression) in &hash.pairs {
+        let key = eval_expression(key_expression, env, depth)?;
+        let key = HashKey::from_object(&key).ok_or(RuntimeError::UnhashableKey {
+            found: key.type_name(),
+            span: key_expression.span(),
+        })?;
+        let value = eval_expression(value_expression, env, depth)?;
+        pairs.insert(key, value);
+    }
+    Ok(Object::Hash(pairs))
+}
+
+fn eval_prefix_expression(
+    prefix: &PrefixExpression,
+    env: &mut Environment,
+    depth: usize,
+) -> EvalResult {
+    let right = eval_expression(&prefix.right, env, depth)?;
+    match (prefix.operator.as_str(), &right) {
+        ("!", _) => Ok(Object::Boolean(!right.is_truthy())),
+        ("-", Object::Integer(value)) => value
+            .checked_neg()
+            .map(Object::Integer)
+            .ok_or(RuntimeError::IntegerOverflow { span: prefix.span }),
+        _ => Err(RuntimeError::UnknownPrefixOperator {
+            operator: prefix.operator.clone(),
+            right: right.type_name(),
+            span: prefix.span,
+        }),
+    }
+}
+
+fn eval_infix_expression(
+    infix: &InfixExpression,
+    env: &mut Environment,
+    depth: usize,
+) -> EvalResult {
+    let left = eval_expression(&infix.left, env, depth)?;
+    let right = eval_expression(&infix.right, env, depth)?;
+    let operator = infix.operator.as_str();
+
+    match (&left, &right) {
+        (Object::Integer(left), Object::Integer(right)) => {
+            eval_integer_infix(operator, *left, *right, infix.span)
+        }
+        (Object::String(left), Object::String(right)) if operator == "+" => {
+            Ok(Object::String(format!("{}{}", left, right)))
+        }
+        _ if left.type_name() != right.type_name() => Err(RuntimeError::TypeMismatch {
+            left: left.type_name(),
+            operator: infix.operator.clone(),
+            right: right.type_name(),
+            span: infix.span,
+        }),
+        _ if operator == "==" => Ok(Object::Boolean(left == right)),
+        _ if operator == "!=" => Ok(Object::Boolean(left != right)),
+        _ => Err(RuntimeError::UnknownInfixOperator {
+            left: left.type_name(),
+            operator: infix.operator.clone(),
+            right: right.type_name(),
+            span: infix.span,
+        }),
+    }
+}
+
+fn eval_integer_infix(operator: &str, left: i64, right: i64, span: Span) -> EvalResult {
+    let arithmetic = match operator {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" if right == 0 => return Err(RuntimeError::DivisionByZero { span }),
+        "/" => left.checked_div(right),
+        "<" => return Ok(Object::Boolean(left < right)),
+        ">" => return Ok(Object::Boolean(left > right)),
+        "==" => return Ok(Object::Boolean(left == right)),
+        "!=" => return Ok(Object::Boolean(left != right)),
+        _ => {
+            return Err(RuntimeError::UnknownInfixOperator {
+                left: "INTEGER",
+                operator: operator.to_string(),
+                right: "INTEGER",
+                span,
+            })
+        }
+    };
+    arithmetic
+        .map(Object::Integer)
+        .ok_or(RuntimeError::IntegerOverflow { span })
+}
+
+fn eval_if_expression(
+    if_expression: &IfExpression,
+    env: &mut Environment,
+    depth: usize,
+) -> EvalResult {
+    let condition = eval_expression(&if_expression.condition, env, depth)?;
+    if condition.is_truthy() {
+        eval_block_statement(&if_expression.consequence, env, depth)
+    } else if let Some(alternative) = &if_expression.alternative {
+        eval_block_statement(alternative, env, depth)
+    } else {
+        Ok(Object::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::Lexer;
+    use parser::Parser;
+
+    fn run(input: &str) -> EvalResult {
+        run_in(input, &mut Environment::new())
+    }
+
+    fn run_in(input: &str, env: &mut Environment) -> EvalResult {
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        assert!(
+            parser.errors().is_empty(),
+            "parser errors for {:?}: {:?}",
+            input,
+            parser.errors()
+        );
+        eval(&program, env)
+    }
+
+    fn assert_evaluates_to(tests: &[(&str, Object)]) {
+        for (input, expected) in tests {
+            assert_eq!(run(input).as_ref(), Ok(expected), "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_eval_integer_expression() {
+        assert_evaluates_to(&[
+            ("5", Object::Integer(5)),
+            ("-10", Object::Integer(-10)),
+            ("--5", Object::Integer(5)),
+            ("5 + 5 + 5 + 5 - 10", Object::Integer(10)),
+            ("2 * 2 * 2 * 2 * 2", Object::Integer(32)),
+            ("-50 + 100 + -50", Object::Integer(0)),
+            ("20 + 2 * -10", Object::Integer(0)),
+            ("50 / 2 * 2 + 10", Object::Integer(60)),
+            ("2 * (5 + 10)", Object::Integer(30)),
+            ("3 * 3 * 3 + 10", Object::Integer(37)),
+            ("(5 + 10 * 2 + 15 / 3) * 2 + -10", Object::Integer(50)),
+            ("7 / 2", Object::Integer(3)),
+        ]);
+    }
+
+    #[test]
+    fn test_eval_boolean_expression() {
+        assert_evaluates_to(&[
+            ("true", Object::Boolean(true)),
+            ("1 < 2", Object::Boolean(true)),
+            ("1 > 2", Object::Boolean(false)),
+            ("1 == 1", Object::Boolean(true)),
+            ("1 != 1", Object::Boolean(false)),
+            ("true == true", Object::Boolean(true)),
+            ("true != false", Object::Boolean(true)),
+            ("(1 < 2) == true", Object::Boolean(true)),
+            ("(1 > 2) == true", Object::Boolean(false)),
+            ("\"a\" == \"a\"", Object::Boolean(true)),
+            ("\"a\" != \"b\"", Object::Boolean(true)),
+        ]);
+    }
+
+    #[test]
+    fn test_bang_operator() {
+        assert_evaluates_to(&[
+            ("!true", Object::Boolean(false)),
+            ("!false", Object::Boolean(true)),
+            ("!5", Object::Boolean(false)),
+            ("!!true", Object::Boolean(true)),
+            ("!!5", Object::Boolean(true)),
+        ]);
+    }
+
+    #[test]
+    fn test_string_concatenation() {
+        assert_evaluates_to(&[(
+            "\"Hello\" + \", \" + \"World!\"",
+            Object::String("Hello, World!".to_string()),
+        )]);
+    }
+
+    #[test]
+    fn test_if_else_expressions() {
+        assert_evaluates_to(&[
+            ("if (true) { 10 }", Object::Integer(10)),
+            ("if (false) { 10 }", Object::Null),
+            ("if (1) { 10 }", Object::Integer(10)),
+            ("if (1 < 2) { 10 }", Object::Integer(10)),
+            ("if (1 > 2) { 10 }", Object::Null),
+            ("if (1 > 2) { 10 } else { 20 }", Object::Integer(20)),
+            ("if (1 < 2) { 10 } else { 20 }", Object::Integer(10)),
+            ("if (true) { }", Object::Null),
+        ]);
+    }
+
+    #[test]
+    fn test_return_statements() {
+        assert_evaluates_to(&[
+            ("return 10;", Object::Integer(10)),
+            ("return 10; 9;", Object::Integer(10)),
+            ("return 2 * 5; 9;", Object::Integer(10)),
+            ("9; return 2 * 5; 9;", Object::Integer(10)),
+            ("return;", Object::Null),
+            (
+                "if (10 > 1) { if (10 > 1) { return 10; } return 1; }",
+                Object::Integer(10),
+            ),
+            (
+                "if (10 > 1) { if (10 > 1) { return 10; } 1; } 2",
+                Object::Integer(10),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_let_statements() {
+        assert_evaluates_to(&[
+            ("let a = 5; a;", Object::Integer(5)),
+            ("let a = 5 * 5; a;", Object::Integer(25)),
+            ("let a = 5; let b = a; b;", Object::Integer(5)),
+            (
+                "let a = 5; let b = a; let c = a + b + 5; c;",
+                Object::Integer(15),
+            ),
+            ("let a = 1; let a = a + 1; a", Object::Integer(2)),
+            ("let a = 5;", Object::Null),
+        ]);
+    }
+
+    #[test]
+    fn test_environment_persists_between_programs() {
+        let mut env = Environment::new();
+        for (input, expected) in [
+            ("let x = 2;", Object::Null),
+            ("x * 21", Object::Integer(42)),
+        ] {
+            let mut parser = Parser::new(Lexer::new(input.as_bytes()));
+            let program = parser.parse_program();
+            assert_eq!(eval(&program, &mut env), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let tests = [
+            ("5 + true;", "1:1: type mismatch: INTEGER + BOOLEAN"),
+            ("5 + true; 5;", "1:1: type mismatch: INTEGER + BOOLEAN"),
+            ("-true", "1:1: unknown operator: -BOOLEAN"),
+            ("true + false;", "1:1: unknown operator: BOOLEAN + BOOLEAN"),
+            (
+                "5; true + false; 5",
+                "1:4: unknown operator: BOOLEAN + BOOLEAN",
+            ),
+            (
+                "if (10 > 1) { true + false; }",
+                "1:15: unknown operator: BOOLEAN + BOOLEAN",
+            ),
+            (
+                "if (10 > 1) {\n  if (10 > 1) { return true + false; }\n  return 1;\n}",
+                "2:24: unknown operator: BOOLEAN + BOOLEAN",
+            ),
+            ("\"a\" - \"b\"", "1:1: unknown operator: STRING - STRING"),
+            ("let x = 1;\nfoobar", "2:1: identifier not found: foobar"),
+            ("1 + 2 / (3 - 3)", "1:5: division by zero"),
+            ("9223372036854775807 + 1", "1:1: integer overflow"),
+            ("let x = 5; x(1)", "1:12: not a function: INTEGER"),
+            (
+                "let f = fn(a, b) { a + b };\nf(1)",
+                "2:1: wrong number of arguments: expected 2, got 1",
+            ),
+            (
+                "let a = [1, 2, 3];\n1 + a[3]",
+                "2:5: index out of range: index 3 but length is 3",
+            ),
+            (
+                "[1][-1]",
+                "1:1: index out of range: index -1 but length is 1",
+            ),
+            ("[][0]", "1:1: index out of range: index 0 but length is 0"),
+            (
+                "1[0]",
+                "1:1: index operator not supported: INTEGER[INTEGER]",
+            ),
+            (
+                "[1][true]",
+                "1:1: index operator not supported: ARRAY[BOOLEAN]",
+            ),
+            (
+                "len(1)",
+                "1:1: argument to `len` not supported, got INTEGER",
+            ),
+            (
+                "len(\"one\", \"two\")",
+                "1:1: wrong number of arguments: expected 1, got 2",
+            ),
+            (
+                "first(1)",
+                "1:1: argument to `first` not supported, got INTEGER",
+            ),
+            (
+                "push([1])",
+                "1:1: wrong number of arguments: expected 2, got 1",
+            ),
+            (
+                "{\"a\": 1,\n fn(x) { x }: 2}",
+                "2:2: unusable as hash key: FUNCTION",
+            ),
+            ("{[1]: 2}", "1:2: unusable as hash key: ARRAY"),
+            ("{}[{}]", "1:1: unusable as hash key: HASH"),
+            ("{1: 2}[len]", "1:1: unusable as hash key: BUILTIN"),
+        ];
+
+        for (input, expected) in tests {
+            match run(input) {
+                Err(error) => assert_eq!(error.to_string(), expected, "input: {}", input),
+                Ok(value) => panic!("expected an error for {}, got {:?}", input, value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_function_application() {
+        assert_evaluates_to(&[
+            (
+                "let identity = fn(x) { x; }; identity(5);",
+                Object::Integer(5),
+            ),
+            (
+                "let identity = fn(x) { return x; }; identity(5);",
+                Object::Integer(5),
+            ),
+            (
+                "let double = fn(x) { x * 2; }; double(5);",
+                Object::Integer(10),
+            ),
+            (
+                "let add = fn(x, y) { x + y; }; add(5, 5);",
+                Object::Integer(10),
+            ),
+            (
+                "let add = fn(x, y) { x + y; }; add(5 + 5, add(5, 5));",
+                Object::Integer(20),
+            ),
+            ("fn(x) { x; }(5)", Object::Integer(5)),
+            ("let f = fn() { return; 1 }; f()", Object::Null),
+            // A return inside the function only leaves the function
+            (
+                "let f = fn() { if (true) { return 1; } 2 }; f() + 10",
+                Object::Integer(11),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_lexical_scoping() {
+        assert_evaluates_to(&[
+            // The body sees `x` from where the function was defined
+            (
+                "let x = 1; let f = fn() { x }; let g = fn(x) { f() }; g(2)",
+                Object::Integer(1),
+            ),
+            // Parameters shadow outer bindings without changing them
+            (
+                "let x = 1; let f = fn(x) { x * 10 }; f(2) + x",
+                Object::Integer(21),
+            ),
+            // Bindings made inside a call do not leak out of it
+            (
+                "let f = fn() { let inner = 3; inner }; f(); let inner = 4; inner",
+                Object::Integer(4),
+            ),
+            (
+                "let new_adder = fn(x) { fn(y) { x + y } }; let add_two = new_adder(2); add_two(3)",
+                Object::Integer(5),
+            ),
+        ]);
+
+        match run("let f = fn() { inner }; let g = fn() { let inner = 1; f() }; g()") {
+            Err(error) => assert_eq!(error.message(), "identifier not found: inner"),
+            Ok(value) => panic!("expected an error, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_array_literals_and_indexing() {
+        assert_evaluates_to(&[
+            (
+                "[1, 2 * 2, 3 + 3]",
+                Object::Array(vec![
+                    Object::Integer(1),
+                    Object::Integer(4),
+                    Object::Integer(6),
+                ]),
+            ),
+            ("[1, 2, 3][0]", Object::Integer(1)),
+            ("[1, 2, 3][2]", Object::Integer(3)),
+            ("let i = 0; [1][i]", Object::Integer(1)),
+            ("[1, 2, 3][1 + 1]", Object::Integer(3)),
+            ("let a = [1, 2, 3]; a[0] + a[1] + a[2]", Object::Integer(6)),
+            ("[[1, 2], [3]][0][1]", Object::Integer(2)),
+            ("[fn(x) { x * 2 }][0](4)", Object::Integer(8)),
+            ("[1, 2] == [1, 2]", Object::Boolean(true)),
+            ("[1, 2] != [2, 1]", Object::Boolean(true)),
+        ]);
+    }
+
+    #[test]
+    fn test_hash_literals() {
+        let program = "let two = \"two\"; {\"one\": 10 - 9, two: 1 + 1, \"thr\" + \"ee\": 6 / 2, 4: 4, true: 5, false: 6}";
+        let pairs = match run(program) {
+            Ok(Object::Hash(pairs)) => pairs,
+            other => panic!("expected a hash, got {:?}", other),
+        };
+
+        let expected = [
+            (HashKey::String("one".to_string()), 1),
+            (HashKey::String("two".to_string()), 2),
+            (HashKey::String("three".to_string()), 3),
+            (HashKey::Integer(4), 4),
+            (HashKey::Boolean(true), 5),
+            (HashKey::Boolean(false), 6),
+        ];
+        assert_eq!(pairs.len(), expected.len());
+        for (key, value) in expected {
+            assert_eq!(
+                pairs.get(&key),
+                Some(&Object::Integer(value)),
+                "key: {}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_index_expressions() {
+        assert_evaluates_to(&[
+            ("{\"foo\": 5}[\"foo\"]", Object::Integer(5)),
+            ("{\"foo\": 5}[\"bar\"]", Object::Null),
+            ("let key = \"foo\"; {\"foo\": 5}[key]", Object::Integer(5)),
+            ("{}[\"foo\"]", Object::Null),
+            ("{5: 5}[5]", Object::Integer(5)),
+            ("{true: 5}[true]", Object::Integer(5)),
+            ("{false: 5}[false]", Object::Integer(5)),
+            // Keys compare by value and type
+            (
+                "{1: \"int\", \"1\": \"str\"}[\"1\"]",
+                Object::String("str".to_string()),
+            ),
+            ("{1: 1, 1: 2}[1]", Object::Integer(2)),
+            (
+                "let h = {\"f\": fn(x) { x * 2 }}; h[\"f\"](21)",
+                Object::Integer(42),
+            ),
+            ("{\"a\": [1, 2]}[\"a\"][1]", Object::Integer(2)),
+            ("{1: 2, 3: 4} == {3: 4, 1: 2}", Object::Boolean(true)),
+        ]);
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let array =
+            |values: &[i64]| Object::Array(values.iter().copied().map(Object::Integer).collect());
+        assert_evaluates_to(&[
+            ("len(\"\")", Object::Integer(0)),
+            ("len(\"four\")", Object::Integer(4)),
+            ("len(\"سلام\")", Object::Integer(4)),
+            ("len([1, 2, 3])", Object::Integer(3)),
+            ("len([])", Object::Integer(0)),
+            ("first([1, 2, 3])", Object::Integer(1)),
+            ("first([])", Object::Null),
+            ("last([1, 2, 3])", Object::Integer(3)),
+            ("last([])", Object::Null),
+            ("rest([1, 2, 3])", array(&[2, 3])),
+            ("rest([1])", array(&[])),
+            ("rest([])", Object::Null),
+            ("push([], 1)", array(&[1])),
+            // push leaves the original array unchanged
+            ("let a = [1]; let b = push(a, 2); a", array(&[1])),
+            ("let a = [1]; let b = push(a, 2); b", array(&[1, 2])),
+            // User bindings shadow builtins
+            ("let len = fn(x) { 42 }; len([])", Object::Integer(42)),
+            (
+                "let map = fn(arr, f) { if (len(arr) == 0) { [] } else { push(map(rest(arr), f), f(first(arr))) } }; map([1, 2, 3], fn(x) { x * 2 })",
+                array(&[6, 4, 2]),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_display() {
+        let tests = [
+            ("5", "5"),
+            ("-5", "-5"),
+            ("1 < 2", "true"),
+            ("\"hi\"", "hi"),
+            ("if (false) { 1 }", "null"),
+            ("fn(x, y) { x + y; }", "fn(x, y) { (x + y) }"),
+            ("[1, \"a\", [true]]", "[1, a, [true]]"),
+            ("len", "builtin function len"),
+            ("{}", "{}"),
+            (
+                "{\"b\": 1, true: [2], 3: \"c\", \"a\": 4}",
+                "{3: c, true: [2], a: 4, b: 1}",
+            ),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(
+                run(input).unwrap().to_string(),
+                expected,
+                "input: {}",
+                input
+            );
+        }
+
+        let error = run("-true").unwrap_err();
+        assert_eq!(
+            Object::from(error).to_string(),
+            "ERROR: unknown operator: -BOOLEAN"
+        );
+    }
+
+    #[test]
+    fn test_closures() {
+        assert_evaluates_to(&[
+            (
+                "let new_adder = fn(x) { fn(y) { x + y } }; let add_two = new_adder(2); add_two(3)",
+                Object::Integer(5),
+            ),
+            // Currying, with every intermediate closure dropped right after the call
+            (
+                "let add = fn(a) { fn(b) { fn(c) { a + b + c } } }; add(1)(2)(3)",
+                Object::Integer(6),
+            ),
+            (
+                "let apply_twice = fn(f, x) { f(f(x)) }; let inc = fn(n) { n + 1 }; apply_twice(inc, 5)",
+                Object::Integer(7),
+            ),
+            // Closures see bindings made in their scope after they were created
+            ("let f = fn() { later }; let later = 4; f()", Object::Integer(4)),
+        ]);
+    }
+
+    #[test]
+    fn test_recursion() {
+        assert_evaluates_to(&[
+            (
+                "let counter = fn(x) { if (x > 100) { return x; } else { counter(x + 1); } }; counter(0)",
+                Object::Integer(101),
+            ),
+            (
+                "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15)",
+                Object::Integer(610),
+            ),
+            // A recursive function defined inside another one
+            (
+                "let sum_to = fn(n) { let go = fn(i, total) { if (i > n) { total } else { go(i + 1, total + i) } }; go(1, 0) }; sum_to(10)",
+                Object::Integer(55),
+            ),
+            // A recursive function that escapes the call it was defined in
+            (
+                "let make = fn() { let down = fn(n) { if (n == 0) { 0 } else { down(n - 1) } }; down }; make()(20)",
+                Object::Integer(0),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let countdown = "let c = fn(n) { if (n == 0) { 0 } else { c(n - 1) } };";
+        let tests = [
+            (format!("{} c({})", countdown, MAX_CALL_DEPTH - 1), "0"),
+            (
+                format!("{} c({})", countdown, MAX_CALL_DEPTH),
+                "1:42: maximum call depth of 1000 exceeded",
+            ),
+            (
+                "let f = fn() { f() };\nf()".to_string(),
+                "1:16: maximum call depth of 1000 exceeded",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            // Test threads get far less stack than STACK_SIZE
+            let outcome = std::thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn(move || match run(&input) {
+                    Ok(value) => value.to_string(),
+                    Err(error) => error.to_string(),
+                })
+                .unwrap()
+                .join()
+                .unwrap();
+            assert_eq!(outcome, expected);
+        }
+    }
+
+    #[test]
+    fn test_recursive_definitions_do_not_leak() {
+        let tests = [
+            (
+                "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10)",
+                Object::Integer(55),
+            ),
+            // The global scope holds add_two, which holds the call scope of
+            // new_adder, which encloses the global scope
+            (
+                "let new_adder = fn(x) { fn(y) { x + y } }; let add_two = new_adder(2); add_two(3)",
+                Object::Integer(5),
+            ),
+            // Recursion through an array or a hash
+            (
+                "let fns = [fn(n) { if (n == 0) { 0 } else { fns[0](n - 1) } }]; fns[0](5)",
+                Object::Integer(0),
+            ),
+            (
+                "let f = fn(n) { if (n == 0) { 0 } else { t[\"f\"](n - 1) } }; let t = {\"f\": f}; f(5)",
+                Object::Integer(0),
+            ),
+            // A cycle entirely inside a call scope that has returned
+            (
+                "let g = fn() { let h = fn() { h }; [h] }; let hs = g(); 1",
+                Object::Integer(1),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut env = Environment::new();
+            assert_eq!(run_in(input, &mut env), Ok(expected), "input: {}", input);
+
+            // Nothing but `env` keeps any of its scopes alive
+            let heap = env.downgrade_heap();
+            drop(env);
+            assert!(heap.upgrade().is_none(), "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_function_outliving_its_environment_does_not_leak() {
+        let mut env = Environment::new();
+        let result = run_in("let f = fn() { f }; f", &mut env);
+        let heap = env.downgrade_heap();
+        drop(env);
+
+        // The function still works after its root is gone
+        let function = match result {
+            Ok(Object::Function(function)) => function,
+            other => panic!("expected a function, got {:?}", other),
+        };
+        assert!(function.env().get("f").is_some());
+
+        drop(function);
+        assert!(heap.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_dropping_many_functions_after_their_environment() {
+        let closures = (0..2000)
+            .map(|i| format!("fn() {{ {} }}, adder({})", i, i))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let program = format!("let adder = fn(x) {{ fn(y) {{ x + y }} }}; [{}]", closures);
+
+        let mut env = Environment::new();
+        let functions = match run_in(&program, &mut env) {
+            Ok(Object::Array(functions)) => functions,
+            other => panic!("expected an array, got {:?}", other),
+        };
+        let heap = env.downgrade_heap();
+        drop(env);
+
+        // Each drop must not collect the whole heap again
+        let start = std::time::Instant::now();
+        for function in functions {
+            drop(function);
+        }
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+        assert!(heap.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_garbage_cycles_are_collected_while_running() {
+        // Every call leaves behind a scope that refers to itself through `h`
+        let program = format!(
+            "let churn = fn(n) {{ if (n == 0) {{ 0 }} else {{ let h = fn() {{ h }}; churn(n - 1) }} }}; {}",
+            "churn(40); ".repeat(100)
+        );
+        let mut env = Environment::new();
+        assert_eq!(run_in(&program, &mut env), Ok(Object::Integer(0)));
+        assert!(env.live_scopes() < 1500, "{} scopes", env.live_scopes());
+    }
+}
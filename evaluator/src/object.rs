@@ -0,0 +1,164 @@
+use crate::builtins::Builtin;
+use crate::environment::Environment;
+use crate::error::RuntimeError;
+use ast::{BlockStatement, FunctionLiteral, Identifier};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+// Values produced by evaluating Mazgamet code
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+    Null,
+    Array(Vec<Object>),
+    Hash(HashMap<HashKey, Object>),
+    Function(Function),
+    Builtin(Builtin),
+    // A value on its way out of a function body through `return`; never visible
+    // outside the evaluator
+    ReturnValue(Box<Object>),
+    // A runtime error as a value, for callers that want one Object whatever happens,
+    // e.g. `eval(&program, &mut env).unwrap_or_else(Object::from)`
+    Error(RuntimeError),
+}
+
+// The values that can key a hash: those compared by value, so a key found again
+// later is equal to the one it was stored under
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl HashKey {
+    // None for objects that cannot be used as a key, such as functions and arrays
+    pub fn from_object(object: &Object) -> Option<HashKey> {
+        match object {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::String(value) => Some(HashKey::String(value.clone())),
+            _ => None,
+        }
+    }
+}
+
+// A function value together with the environment the function literal was
+// evaluated in, which its body sees when called: a closure. The literal is shared
+// with the AST, so cloning a function does not copy its body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    literal: Arc<FunctionLiteral>,
+    env: Environment,
+}
+
+impl Function {
+    pub fn new(literal: Arc<FunctionLiteral>, env: Environment) -> Self {
+        Self {
+            literal,
+            env: env.handle(),
+        }
+    }
+
+    pub fn parameters(&self) -> &[Identifier] {
+        &self.literal.parameters
+    }
+
+    pub fn body(&self) -> &BlockStatement {
+        &self.literal.body
+    }
+
+    // The environment the function was defined in
+    pub fn env(&self) -> Environment {
+        self.env.root()
+    }
+
+    pub(crate) fn captured(&self) -> &Environment {
+        &self.env
+    }
+}
+
+impl Object {
+    // The name of the value's type as it appears in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Null => "NULL",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+            Object::ReturnValue(value) => value.type_name(),
+            Object::Error(_) => "ERROR",
+        }
+    }
+
+    // Only `false` and `null` count as false in conditions
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Object::Boolean(false) | Object::Null)
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Null => write!(f, "null"),
+            Object::Array(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| element.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(pairs) => {
+                // Sorted by key, so the same hash always prints the same way
+                let mut pairs = pairs.iter().collect::<Vec<_>>();
+                pairs.sort_by_key(|(key, _)| *key);
+                let pairs = pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Function(function) => function.fmt(f),
+            Object::Builtin(builtin) => builtin.fmt(f),
+            Object::ReturnValue(value) => value.fmt(f),
+            Object::Error(error) => write!(f, "ERROR: {}", error.message()),
+        }
+    }
+}
+
+impl fmt::Display for HashKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashKey::Integer(value) => write!(f, "{}", value),
+            HashKey::Boolean(value) => write!(f, "{}", value),
+            HashKey::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parameters = self
+            .parameters()
+            .iter()
+            .map(|parameter| parameter.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "fn({}) {}", parameters.join(", "), self.body())
+    }
+}
+
+impl From<RuntimeError> for Object {
+    fn from(error: RuntimeError) -> Self {
+        Object::Error(error)
+    }
+}
@@ -0,0 +1,126 @@
+// Compares lexing into owned `Token`s against `BorrowedToken`s on a large generated
+// script, counting heap allocations with a wrapping global allocator.
+//
+//     cargo bench -p lexer --bench tokens
+
+use lexer::{Lexer, TokenType};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: u32 = 20;
+
+// Roughly what a code generator would emit: many small functions and calls
+fn generate_script(functions: usize) -> String {
+    let mut script = String::new();
+    for i in 0..functions {
+        script.push_str(&format!(
+            "let function_{i} = fn(left, right) {{\n    \
+                 if (left < right) {{ return left + {i} * right; }}\n    \
+                 let label = \"function {i}\";\n    \
+                 left - right / 2 == {i}\n\
+             }};\n\
+             let result_{i} = function_{i}(result_{}, 0x{i:X});\n",
+            i.saturating_sub(1)
+        ));
+    }
+    script
+}
+
+struct Measurement {
+    tokens: usize,
+    allocations: usize,
+    bytes: usize,
+    elapsed: Duration,
+}
+
+fn measure(input: &str, lex: impl Fn(&str) -> usize) -> Measurement {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    let mut tokens = 0;
+    for _ in 0..ITERATIONS {
+        tokens = black_box(lex(black_box(input)));
+    }
+
+    Measurement {
+        tokens,
+        allocations: (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS as usize,
+        bytes: (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / ITERATIONS as usize,
+        elapsed: start.elapsed() / ITERATIONS,
+    }
+}
+
+fn lex_owned(input: &str) -> usize {
+    let mut lexer = Lexer::new(input.as_bytes());
+    let mut count = 0;
+    loop {
+        let token = black_box(lexer.next_token());
+        count += 1;
+        if token.token_type == TokenType::EOF {
+            return count;
+        }
+    }
+}
+
+fn lex_borrowed(input: &str) -> usize {
+    let mut lexer = Lexer::new(input.as_bytes());
+    let mut count = 0;
+    loop {
+        let token = black_box(lexer.next_borrowed_token());
+        count += 1;
+        if token.token_type == TokenType::EOF {
+            return count;
+        }
+    }
+}
+
+fn report(name: &str, measurement: &Measurement) {
+    println!(
+        "{:<10} {:>8} tokens {:>10} allocations {:>12} bytes {:>10.2?}",
+        name, measurement.tokens, measurement.allocations, measurement.bytes, measurement.elapsed
+    );
+}
+
+fn main() {
+    let script = generate_script(2_000);
+    println!(
+        "lexing {} bytes, averaged over {} runs",
+        script.len(),
+        ITERATIONS
+    );
+
+    let owned = measure(&script, lex_owned);
+    let borrowed = measure(&script, lex_borrowed);
+    report("Token", &owned);
+    report("Borrowed", &borrowed);
+
+    assert_eq!(owned.tokens, borrowed.tokens);
+    println!(
+        "borrowed tokens avoid {} of {} allocations",
+        owned.allocations - borrowed.allocations,
+        owned.allocations
+    );
+}
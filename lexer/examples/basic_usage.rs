@@ -12,16 +12,15 @@ fn main() {
             x + y;
         };
         let result = add(five, ten);
-        !1/*5;
+        !1/ *5;
         5 < 10 > 5;
     "#;
 
     // Create a new lexer instance with the input
-    let mut lexer = Lexer::new(input.as_bytes());
+    let lexer = Lexer::new(input.as_bytes());
 
     // Print the first 10 tokens
-    for i in 0..10 {
-        let token = lexer.next_token();
+    for (i, token) in lexer.take(10).enumerate() {
         println!("Token {}: {:?} - '{}'", i, token.token_type, token.literal);
     }
 }
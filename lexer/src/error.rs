@@ -0,0 +1,89 @@
+use crate::span::Span;
+use std::error::Error;
+use std::fmt;
+
+// Problems found while turning source text into tokens. The lexer records these
+// and keeps going, so one bad literal does not hide the rest of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexError {
+    // A character that cannot start any token, e.g. `@` or a lone `&`
+    UnexpectedCharacter {
+        ch: char,
+        span: Span,
+    },
+    // A string literal still open when the input ended; spans from the opening quote
+    UnterminatedString {
+        span: Span,
+    },
+    // A /* comment still open when the input ended; spans from the opening /*
+    UnterminatedComment {
+        span: Span,
+    },
+    // A backslash sequence inside a string that is not one of \n \t \" \\ \u{...}
+    InvalidEscape {
+        sequence: String,
+        span: Span,
+    },
+    // A number that starts out right but cannot be completed, e.g. `0x` or `1e`
+    InvalidNumber {
+        literal: String,
+        reason: String,
+        span: Span,
+    },
+    // Bytes that are not valid UTF-8; the span covers the offending bytes
+    InvalidUtf8 {
+        span: Span,
+    },
+}
+
+impl LexError {
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::UnexpectedCharacter { span, .. } => *span,
+            LexError::UnterminatedString { span } => *span,
+            LexError::UnterminatedComment { span } => *span,
+            LexError::InvalidEscape { span, .. } => *span,
+            LexError::InvalidNumber { span, .. } => *span,
+            LexError::InvalidUtf8 { span } => *span,
+        }
+    }
+
+    pub(crate) fn span_mut(&mut self) -> &mut Span {
+        match self {
+            LexError::UnexpectedCharacter { span, .. } => span,
+            LexError::UnterminatedString { span } => span,
+            LexError::UnterminatedComment { span } => span,
+            LexError::InvalidEscape { span, .. } => span,
+            LexError::InvalidNumber { span, .. } => span,
+            LexError::InvalidUtf8 { span } => span,
+        }
+    }
+
+    // The error text without its location
+    pub fn message(&self) -> String {
+        match self {
+            LexError::UnexpectedCharacter { ch, .. } => format!("unexpected character `{}`", ch),
+            LexError::UnterminatedString { .. } => "unterminated string literal".to_string(),
+            LexError::UnterminatedComment { .. } => "unterminated block comment".to_string(),
+            LexError::InvalidEscape { sequence, .. } => {
+                format!("invalid escape sequence `{}`", sequence)
+            }
+            LexError::InvalidNumber {
+                literal, reason, ..
+            } => {
+                format!("invalid number literal `{}`: {}", literal, reason)
+            }
+            LexError::InvalidUtf8 { span } => {
+                format!("invalid UTF-8 at byte offset {}", span.start)
+            }
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span(), self.message())
+    }
+}
+
+impl Error for LexError {}
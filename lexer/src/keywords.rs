@@ -0,0 +1,139 @@
+use crate::token::TokenType;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use unicode_normalization::UnicodeNormalization;
+
+const ENGLISH: &[(&str, TokenType)] = &[
+    ("fn", TokenType::Function),
+    ("let", TokenType::Let),
+    ("true", TokenType::True),
+    ("false", TokenType::False),
+    ("if", TokenType::IF),
+    ("else", TokenType::ELSE),
+    ("return", TokenType::Return),
+];
+
+// Words with a yeh or kaf are listed with both the Persian letters and the Arabic
+// ones (ي, ك) that many keyboards still produce
+const PERSIAN: &[(&str, TokenType)] = &[
+    ("تابع", TokenType::Function),
+    ("بگذار", TokenType::Let),
+    ("درست", TokenType::True),
+    ("صحیح", TokenType::True),
+    ("صحيح", TokenType::True),
+    ("نادرست", TokenType::False),
+    ("غلط", TokenType::False),
+    ("اگر", TokenType::IF),
+    ("وگرنه", TokenType::ELSE),
+    ("برگردان", TokenType::Return),
+];
+
+static ENGLISH_TABLE: LazyLock<KeywordTable> = LazyLock::new(KeywordTable::english);
+
+// Maps identifier spellings to keyword token types. Any number of spellings can
+// stand for the same keyword, so a table can mix languages or accept variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordTable {
+    keywords: HashMap<String, TokenType>,
+}
+
+impl KeywordTable {
+    // A table without keywords, where every word is an identifier
+    pub fn new() -> Self {
+        Self {
+            keywords: HashMap::new(),
+        }
+    }
+
+    // fn, let, true, false, if, else and return; what Lexer::new uses
+    pub fn english() -> Self {
+        Self::from_list(ENGLISH)
+    }
+
+    // The same keywords in Persian, e.g. `بگذار` for let and `اگر` for if
+    pub fn persian() -> Self {
+        Self::from_list(PERSIAN)
+    }
+
+    fn from_list(list: &[(&str, TokenType)]) -> Self {
+        list.iter()
+            .fold(Self::new(), |table, (spelling, token_type)| {
+                table.with(spelling, token_type.clone())
+            })
+    }
+
+    // Adds `spelling` as another way to write `token_type`, replacing whatever it
+    // meant before. Spellings are normalized the same way the lexer normalizes
+    // identifiers, so they match however the source composes its characters.
+    pub fn insert(&mut self, spelling: &str, token_type: TokenType) {
+        self.keywords.insert(spelling.nfc().collect(), token_type);
+    }
+
+    pub fn with(mut self, spelling: &str, token_type: TokenType) -> Self {
+        self.insert(spelling, token_type);
+        self
+    }
+
+    // Adds every spelling of `other`, e.g. `KeywordTable::english().merge(&KeywordTable::persian())`
+    pub fn merge(mut self, other: &KeywordTable) -> Self {
+        self.keywords.extend(
+            other
+                .keywords
+                .iter()
+                .map(|(spelling, token_type)| (spelling.clone(), token_type.clone())),
+        );
+        self
+    }
+
+    pub fn lookup(&self, ident: &str) -> TokenType {
+        self.keywords
+            .get(ident)
+            .cloned()
+            .unwrap_or(TokenType::Identifier)
+    }
+}
+
+impl Default for KeywordTable {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+// The shared English table, built once
+pub(crate) fn english_keywords() -> &'static KeywordTable {
+    &ENGLISH_TABLE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let table = KeywordTable::english().merge(&KeywordTable::persian());
+
+        assert_eq!(table.lookup("let"), TokenType::Let);
+        assert_eq!(table.lookup("بگذار"), TokenType::Let);
+        assert_eq!(table.lookup("اگر"), TokenType::IF);
+        assert_eq!(table.lookup("letter"), TokenType::Identifier);
+
+        // Several spellings of the same keyword
+        assert_eq!(table.lookup("درست"), TokenType::True);
+        assert_eq!(table.lookup("صحیح"), TokenType::True);
+        assert_eq!(table.lookup("صحيح"), TokenType::True);
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let table = KeywordTable::new()
+            .with("func", TokenType::Function)
+            .with("fn", TokenType::Function)
+            .with("cafe\u{301}", TokenType::Let);
+
+        assert_eq!(table.lookup("func"), TokenType::Function);
+        assert_eq!(table.lookup("fn"), TokenType::Function);
+        assert_eq!(table.lookup("let"), TokenType::Identifier);
+        // Stored normalized, like the identifiers it is matched against
+        assert_eq!(table.lookup("caf\u{e9}"), TokenType::Let);
+    }
+}
@@ -1,42 +1,154 @@
-use crate::token::{Token, TokenType};
+use crate::error::LexError;
+use crate::keywords::{KeywordTable, english_keywords};
+use crate::span::Span;
+use crate::token::{BorrowedToken, Token, TokenType};
 use log::{debug, trace};
+use std::borrow::Cow;
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+use unicode_xid::UnicodeXID;
+
+// Every operator and punctuation token, longest spellings first so that the first
+// match is also the longest one (maximal munch): `<=` wins over `<`, `**` over `*`
+const OPERATORS: &[(&str, TokenType)] = &[
+    ("==", TokenType::EQ),
+    ("!=", TokenType::NotEQ),
+    ("<=", TokenType::LTEQ),
+    (">=", TokenType::GTEQ),
+    ("&&", TokenType::And),
+    ("||", TokenType::Or),
+    ("**", TokenType::Power),
+    ("+=", TokenType::PlusAssign),
+    ("-=", TokenType::MinusAssign),
+    ("*=", TokenType::AsteriskAssign),
+    ("/=", TokenType::SlashAssign),
+    ("->", TokenType::Arrow),
+    ("=>", TokenType::FatArrow),
+    ("=", TokenType::Assign),
+    ("+", TokenType::Plus),
+    ("-", TokenType::Minus),
+    ("!", TokenType::Bang),
+    ("*", TokenType::Asterisk),
+    ("/", TokenType::Slash),
+    ("%", TokenType::Percent),
+    ("<", TokenType::LT),
+    (">", TokenType::GT),
+    (",", TokenType::Comma),
+    (";", TokenType::Semicolon),
+    (".", TokenType::Dot),
+    (":", TokenType::Colon),
+    ("(", TokenType::LeftParen),
+    (")", TokenType::RightParen),
+    ("{", TokenType::LeftBrace),
+    ("}", TokenType::RightBrace),
+    ("[", TokenType::LeftBracket),
+    ("]", TokenType::RightBracket),
+];
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
     input: &'a [u8],
-    position: usize,
-    read_position: usize,
-    ch: Option<u8>,
+    position: usize,      // Byte offset of `ch`
+    read_position: usize, // Byte offset just past `ch`
+    ch: Option<char>,
+    invalid: bool, // `ch` stands in for bytes that are not valid UTF-8
+    line: usize,   // 1-based line of `ch`
+    column: usize, // 1-based column of `ch`, counted in characters
+    errors: Vec<LexError>,
+    emit_comments: bool, // Return comments as tokens instead of skipping them
+    exhausted: bool,     // The iterator has already handed out the EOF token
+    keywords: &'a KeywordTable,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a [u8]) -> Self {
+        Self::with_keywords(input, english_keywords())
+    }
+
+    // A lexer that recognizes the keywords in `keywords` instead of the English ones
+    pub fn with_keywords(input: &'a [u8], keywords: &'a KeywordTable) -> Self {
+        let lexer = Self::resume(input, 1, 1, keywords);
+        debug!("Created lexer: {:?}", lexer);
+        lexer
+    }
+
+    // A lexer for text that continues a document, its first character sitting at
+    // `line` and `column` of it. Spans are still offsets into `input`.
+    pub(crate) fn resume(
+        input: &'a [u8],
+        line: usize,
+        column: usize,
+        keywords: &'a KeywordTable,
+    ) -> Self {
         let mut lexer = Self {
             input,
             position: 0,
             read_position: 0,
             ch: None,
+            invalid: false,
+            line,
+            column: column - 1, // read_char moves onto the first character
+            errors: Vec::new(),
+            emit_comments: false,
+            exhausted: false,
+            keywords,
         };
         lexer.read_char();
-        debug!("Created lexer: {:?}", lexer);
         lexer
     }
 
+    // Byte offset, line and column of the next character to be lexed
+    pub(crate) fn location(&self) -> (usize, usize, usize) {
+        (self.position, self.line, self.column)
+    }
+
+    // Lexes all of `input`, EOF token included, or returns the first error found
+    pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
+        let mut lexer = Lexer::new(input.as_bytes());
+        let tokens: Vec<Token> = lexer.by_ref().collect();
+        match lexer.take_errors().into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(tokens),
+        }
+    }
+
+    // Comments are skipped like whitespace unless this is turned on, in which case
+    // each one comes back as a TokenType::Comment token holding its full text
+    pub fn emit_comments(mut self, emit: bool) -> Self {
+        self.emit_comments = emit;
+        self
+    }
+
     pub fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            trace!("read_char: EOF at position {}", self.read_position);
-            self.ch = None; // EOF
+        if self.ch == Some('\n') {
+            self.line += 1;
+            self.column = 1;
         } else {
-            // Direct byte access - much more efficient than chars() iterator
-            self.ch = Some(self.input[self.read_position]);
-            trace!(
-                "read_char: '{}' at position {}",
-                self.ch.unwrap() as char,
-                self.read_position
-            );
+            self.column += 1;
         }
+
         self.position = self.read_position;
-        self.read_position += 1; // Always advance by 1 byte
+        match decode_char(self.input, self.position) {
+            None => {
+                trace!("read_char: EOF at position {}", self.position);
+                self.ch = None;
+                self.invalid = false;
+            }
+            Some(Ok((ch, width))) => {
+                trace!("read_char: '{}' at position {}", ch, self.position);
+                self.ch = Some(ch);
+                self.invalid = false;
+                self.read_position += width;
+            }
+            Some(Err(width)) => {
+                debug!("Invalid UTF-8 at byte offset {}", self.position);
+                self.ch = Some(char::REPLACEMENT_CHARACTER);
+                self.invalid = true;
+                self.read_position += width;
+                self.errors.push(LexError::InvalidUtf8 {
+                    span: Span::new(self.position, self.read_position, self.line, self.column),
+                });
+            }
+        }
         trace!(
             "read_char: new position={}, read_position={}",
             self.position, self.read_position
@@ -44,155 +156,315 @@ impl<'a> Lexer<'a> {
     }
 
     pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
-        debug!(
-            "Current character: {:?}, position: {}, read_position: {}",
-            self.ch.map(|c| c as char),
-            self.position,
-            self.read_position
-        );
-        let token = match self.ch {
-            Some(b'=') => {
-                debug!("Found ASSIGN or EQ token");
-                if let Some('=') = self.peek_char() {
-                    self.read_char();
+        self.next_borrowed_token().into_owned()
+    }
+
+    // Like next_token, but the literal borrows from the input wherever it is spelled
+    // exactly as in the source, which is everything except strings with escapes and
+    // identifiers that needed normalizing. Saves an allocation per token.
+    pub fn next_borrowed_token(&mut self) -> BorrowedToken<'a> {
+        // Characters that cannot start a token are reported and skipped, so this
+        // loops until it finds one that can
+        loop {
+            self.skip_whitespace();
+            // Invalid bytes are already reported, so they are skipped like whitespace
+            while self.invalid || (!self.emit_comments && self.at_comment_start()) {
+                if self.invalid {
                     self.read_char();
-                    Token::new(TokenType::EQ, "==".to_string())
                 } else {
-                    self.read_char();
-                    Token::new_char(TokenType::Assign, '=')
+                    self.read_comment();
                 }
+                self.skip_whitespace();
             }
-            Some(b'!') => {
-                debug!("Found BANG or NotEQ token");
-                if let Some('=') = self.peek_char() {
-                    self.read_char();
-                    self.read_char();
-                    Token::new(TokenType::NotEQ, "!=".to_string())
-                } else {
-                    self.read_char();
-                    Token::new_char(TokenType::Bang, '!')
+            debug!(
+                "Current character: {:?}, position: {}, read_position: {}",
+                self.ch, self.position, self.read_position
+            );
+            let (start, line, column) = (self.position, self.line, self.column);
+            let token = match self.ch {
+                Some('/') if self.at_comment_start() => {
+                    debug!("Found COMMENT token");
+                    let literal = self.read_comment();
+                    BorrowedToken::new(TokenType::Comment, literal)
                 }
-            }
-            Some(b';') => {
-                debug!("Found SEMICOLON token");
-                self.read_char();
-                Token::new_char(TokenType::Semicolon, ';')
-            }
-            Some(b'(') => {
-                debug!("Found LEFT_PAREN token");
-                self.read_char();
-                Token::new_char(TokenType::LeftParen, '(')
-            }
-            Some(b')') => {
-                debug!("Found RIGHT_PAREN token");
-                self.read_char();
-                Token::new_char(TokenType::RightParen, ')')
-            }
-            Some(b',') => {
-                debug!("Found COMMA token");
-                self.read_char();
-                Token::new_char(TokenType::Comma, ',')
-            }
-            Some(b'+') => {
-                debug!("Found PLUS token");
-                self.read_char();
-                Token::new_char(TokenType::Plus, '+')
-            }
-            Some(b'-') => {
-                debug!("Found MINUS token");
-                self.read_char();
-                Token::new_char(TokenType::Minus, '-')
-            }
-            Some(b'/') => {
-                debug!("Found SLASH token");
-                self.read_char();
-                Token::new_char(TokenType::Slash, '/')
-            }
-            Some(b'*') => {
-                debug!("Found ASTERISK token");
-                self.read_char();
-                Token::new_char(TokenType::Asterisk, '*')
-            }
-            Some(b'<') => {
-                debug!("Found LT token");
-                self.read_char();
-                Token::new_char(TokenType::LT, '<')
-            }
-            Some(b'>') => {
-                debug!("Found GT token");
+                Some('"') => {
+                    debug!("Found STRING token");
+                    let literal = self.read_string();
+                    debug!("Read string: {:?}", literal);
+                    BorrowedToken::new(TokenType::String, literal)
+                }
+                Some(ch) if Self::is_letter(ch) => {
+                    debug!("Found letter character: {}", ch);
+                    let literal = self.read_identifier();
+                    let token_type = self.keywords.lookup(&literal);
+                    debug!("Read identifier: {}, token type: {:?}", literal, token_type);
+                    BorrowedToken::new(token_type, literal)
+                }
+                Some(ch) if Self::is_digit(ch) => {
+                    debug!("Found digit character: {}", ch);
+                    let (token_type, literal) = self.read_number();
+                    debug!("Read number: {}, token type: {:?}", literal, token_type);
+                    BorrowedToken::new(token_type, literal)
+                }
+                Some(ch) => match self.read_operator() {
+                    Some(token) => token,
+                    None => {
+                        debug!("Found unexpected character: {}", ch);
+                        self.read_char();
+                        self.errors.push(LexError::UnexpectedCharacter {
+                            ch,
+                            span: Span::new(start, self.position, line, column),
+                        });
+                        continue;
+                    }
+                },
+                #[allow(non_snake_case)]
+                None => {
+                    debug!("Found EOF token");
+                    BorrowedToken::new(TokenType::EOF, "")
+                }
+            };
+            let token = token.with_span(Span::new(start, self.position, line, column));
+            debug!(
+                "Returning token: {:?} with literal: {} at {}",
+                token.token_type, token.literal, token.span
+            );
+            return token;
+        }
+    }
+
+    // Reads the longest operator starting at `ch`, if there is one
+    fn read_operator(&mut self) -> Option<BorrowedToken<'a>> {
+        let rest = &self.input[self.position..];
+        let (spelling, token_type) = OPERATORS
+            .iter()
+            .find(|(spelling, _)| rest.starts_with(spelling.as_bytes()))?;
+        debug!("Found {:?} token", token_type);
+        // Operators are ASCII, so each byte is one character
+        for _ in 0..spelling.len() {
+            self.read_char();
+        }
+        Some(BorrowedToken::new(token_type.clone(), *spelling))
+    }
+
+    // Identifiers are returned in Unicode normalization form C, so two spellings of
+    // the same name (precomposed or with combining marks) refer to the same binding
+    pub fn read_identifier(&mut self) -> Cow<'a, str> {
+        let position = self.position;
+        trace!("Starting read_identifier at position: {}", position);
+        while let Some(ch) = self.ch {
+            if position == self.position || Self::is_identifier_continue(ch) {
+                trace!("Reading identifier character: {}", ch);
                 self.read_char();
-                Token::new_char(TokenType::GT, '>')
+            } else {
+                trace!("Stopping at non-identifier character: {:?}", self.ch);
+                break;
             }
-            Some(b'{') => {
-                debug!("Found LEFT_BRACE token");
-                self.read_char();
-                Token::new_char(TokenType::LeftBrace, '{')
+        }
+        let text = self.slice(position, self.position);
+        let result = if text.is_ascii() || is_nfc(&text) {
+            text
+        } else {
+            Cow::Owned(text.nfc().collect())
+        };
+        trace!(
+            "Identifier read: {}, new position: {}",
+            result, self.position
+        );
+        result
+    }
+
+    // Reads a double-quoted string starting at the opening quote and returns its
+    // unescaped contents. Malformed strings are recorded in `errors` and still
+    // produce a value, so parsing can carry on past them.
+    pub fn read_string(&mut self) -> Cow<'a, str> {
+        let (start, line, column) = (self.position, self.line, self.column);
+        trace!("Starting read_string at position: {}", start);
+        self.read_char(); // Skip the opening quote
+
+        // The contents are borrowed from the input until the first escape, which
+        // switches over to building an owned copy
+        let contents_start = self.position;
+        let contents_end;
+        let mut unescaped: Option<String> = None;
+        loop {
+            match self.ch {
+                Some('"') => {
+                    contents_end = self.position;
+                    self.read_char(); // Skip the closing quote
+                    break;
+                }
+                Some('\\') => {
+                    let value = unescaped.get_or_insert_with(|| {
+                        self.slice(contents_start, self.position).into_owned()
+                    });
+                    self.read_escape(value);
+                }
+                Some(ch) => {
+                    if let Some(value) = unescaped.as_mut() {
+                        value.push(ch);
+                    }
+                    self.read_char();
+                }
+                None => {
+                    contents_end = self.position;
+                    debug!("Unterminated string starting at {}:{}", line, column);
+                    self.errors.push(LexError::UnterminatedString {
+                        span: Span::new(start, self.position, line, column),
+                    });
+                    break;
+                }
             }
-            Some(b'}') => {
-                debug!("Found RIGHT_BRACE token");
-                self.read_char();
-                Token::new_char(TokenType::RightBrace, '}')
+        }
+
+        let value = match unescaped {
+            Some(value) => Cow::Owned(value),
+            None => self.slice(contents_start, contents_end),
+        };
+        trace!("String read: {:?}, new position: {}", value, self.position);
+        value
+    }
+
+    // Reads one escape sequence starting at the backslash and appends the character
+    // it stands for. An invalid sequence is recorded in `errors` and kept verbatim.
+    fn read_escape(&mut self, value: &mut String) {
+        let (start, line, column) = (self.position, self.line, self.column);
+        self.read_char(); // Skip the backslash
+
+        let escaped = match self.ch {
+            // The unterminated string is reported by read_string
+            None => return,
+            Some('u') => {
+                self.read_char(); // Skip 'u'
+                self.read_unicode_escape()
             }
             Some(ch) => {
-                if Self::is_letter(ch) {
-                    debug!("Found letter character: {}", ch as char);
-                    let literal = self.read_identifier();
-                    let token_type = TokenType::lookup_ident(&literal);
-                    debug!("Read identifier: {}, token type: {:?}", literal, token_type);
-                    Token::new(token_type, literal)
-                } else if Self::is_digit(ch) {
-                    debug!("Found digit character: {}", ch as char);
-                    let literal = self.read_number();
-                    debug!("Read number: {}", literal);
-                    Token::new(TokenType::Integer, literal)
-                } else {
-                    debug!("Found ILLEGAL character: {}", ch as char);
-                    self.read_char();
-                    // Convert byte to char for the token
-                    let ch_as_char = ch as char;
-                    Token::new_char(TokenType::Illegal, ch_as_char)
+                self.read_char(); // Skip the escaped character
+                match ch {
+                    'n' => Some('\n'),
+                    't' => Some('\t'),
+                    '"' => Some('"'),
+                    '\\' => Some('\\'),
+                    _ => None,
                 }
             }
-            #[allow(non_snake_case)]
+        };
+
+        match escaped {
+            Some(ch) => value.push(ch),
             None => {
-                debug!("Found EOF token");
-                Token::new(TokenType::EOF, "".to_string())
+                let sequence = String::from_utf8_lossy(&self.input[start..self.position]);
+                debug!("Invalid escape sequence: {:?}", sequence);
+                value.push_str(&sequence);
+                self.errors.push(LexError::InvalidEscape {
+                    sequence: sequence.into_owned(),
+                    span: Span::new(start, self.position, line, column),
+                });
             }
-        };
-        debug!(
-            "Returning token: {:?} with literal: {}",
-            token.token_type, token.literal
-        );
-        token
+        }
     }
 
-    pub fn read_identifier(&mut self) -> String {
-        let position = self.position;
-        trace!("Starting read_identifier at position: {}", position);
+    // Reads the `{1F600}` part of a \u escape, up to and including the closing brace.
+    // Returns None unless it names a valid Unicode scalar value in 1 to 6 hex digits.
+    fn read_unicode_escape(&mut self) -> Option<char> {
+        if self.ch != Some('{') {
+            return None;
+        }
+        self.read_char(); // Skip '{'
+
+        let digits_start = self.position;
         while let Some(ch) = self.ch {
-            if Self::is_letter(ch) {
-                trace!("Reading letter: {}", ch as char);
+            if ch.is_ascii_hexdigit() {
                 self.read_char();
             } else {
-                trace!("Stopping at non-letter: {:?}", self.ch.map(|c| c as char));
                 break;
             }
         }
-        let result = String::from_utf8(self.input[position..self.position].to_vec())
-            .unwrap_or_else(|_| String::new());
+        let digits = &self.input[digits_start..self.position];
+
+        if self.ch != Some('}') {
+            return None;
+        }
+        self.read_char(); // Skip '}'
+
+        if digits.is_empty() || digits.len() > 6 {
+            return None;
+        }
+        let digits = std::str::from_utf8(digits).ok()?;
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    fn at_comment_start(&self) -> bool {
+        self.ch == Some('/') && matches!(self.peek_byte(), Some(b'/') | Some(b'*'))
+    }
+
+    // Reads a `// ...` comment up to the end of its line, or a `/* ... */` comment
+    // including any comments nested inside it, and returns its full text
+    pub fn read_comment(&mut self) -> Cow<'a, str> {
+        let (start, line, column) = (self.position, self.line, self.column);
+        trace!("Starting read_comment at position: {}", start);
+        self.read_char(); // Skip the first '/'
+
+        if self.ch == Some('/') {
+            while !matches!(self.ch, None | Some('\n')) {
+                self.read_char();
+            }
+        } else {
+            self.read_char(); // Skip the opening '*'
+            let mut depth = 1;
+            while depth > 0 {
+                match (self.ch, self.peek_byte()) {
+                    (Some('/'), Some(b'*')) => {
+                        depth += 1;
+                        self.read_char();
+                    }
+                    (Some('*'), Some(b'/')) => {
+                        depth -= 1;
+                        self.read_char();
+                    }
+                    (None, _) => {
+                        debug!("Unterminated block comment starting at {}:{}", line, column);
+                        self.errors.push(LexError::UnterminatedComment {
+                            span: Span::new(start, self.position, line, column),
+                        });
+                        break;
+                    }
+                    _ => {}
+                }
+                self.read_char();
+            }
+        }
+
+        let result = self.slice(start, self.position);
         trace!(
-            "Identifier read: {}, new position: {}",
+            "Comment read: {:?}, new position: {}",
             result, self.position
         );
         result
     }
 
-    pub fn is_letter(ch: u8) -> bool {
-        ch.is_ascii_lowercase() || ch.is_ascii_uppercase() || ch == b'_'
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
+    // Hands over the errors collected so far, leaving the lexer's list empty
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // Whether `ch` can start an identifier: `_` or any Unicode XID_Start character
+    pub fn is_letter(ch: char) -> bool {
+        ch == '_' || ch.is_xid_start()
     }
 
-    fn is_digit(ch: u8) -> bool {
+    // Whether `ch` can appear after the first character of an identifier
+    pub fn is_identifier_continue(ch: char) -> bool {
+        ch.is_xid_continue()
+    }
+
+    fn is_digit(ch: char) -> bool {
         ch.is_ascii_digit()
     }
 
@@ -200,8 +472,8 @@ impl<'a> Lexer<'a> {
         trace!("Starting to skip whitespace");
         let initial_pos = self.position;
         while let Some(ch) = self.ch {
-            if ch == b' ' || ch == b'\t' || ch == b'\n' || ch == b'\r' {
-                trace!("Skipping whitespace character: '{}'", ch as char);
+            if ch == ' ' || ch == '\t' || ch == '\n' || ch == '\r' {
+                trace!("Skipping whitespace character: {:?}", ch);
                 self.read_char();
             } else {
                 break;
@@ -217,39 +489,135 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    pub fn read_number(&mut self) -> String {
-        let position = self.position;
-        trace!("Starting read_number at position: {}", position);
+    // Reads an integer (decimal, or hex/octal/binary after a 0x/0o/0b prefix) or a
+    // decimal float with an optional exponent. Digits may be separated by `_`. The
+    // literal is returned as written; malformed ones are recorded in `errors`.
+    pub fn read_number(&mut self) -> (TokenType, Cow<'a, str>) {
+        let (start, line, column) = (self.position, self.line, self.column);
+        trace!("Starting read_number at position: {}", start);
+
+        let prefixed = match (self.ch, self.peek_byte()) {
+            (Some('0'), Some(b'x' | b'X')) => Some((16, "hexadecimal")),
+            (Some('0'), Some(b'o' | b'O')) => Some((8, "octal")),
+            (Some('0'), Some(b'b' | b'B')) => Some((2, "binary")),
+            _ => None,
+        };
+
+        let mut token_type = TokenType::Integer;
+        let mut problem = None;
+        if let Some((radix, name)) = prefixed {
+            self.read_char();
+            self.read_char(); // Skip the prefix
+            let digits_start = self.position;
+            // Take every alphanumeric so `0b102` is one bad literal rather than `0b10` and `2`
+            while matches!(self.ch, Some(ch) if ch.is_ascii_alphanumeric() || ch == '_') {
+                self.read_char();
+            }
+            let digits = &self.input[digits_start..self.position];
+            if let Some(&bad) = digits
+                .iter()
+                .find(|&&byte| byte != b'_' && !(byte as char).is_digit(radix))
+            {
+                problem = Some(format!(
+                    "invalid digit `{}` in {} literal",
+                    bad as char, name
+                ));
+            } else if digits.iter().all(|&byte| byte == b'_') {
+                problem = Some(format!("expected {} digits after the prefix", name));
+            }
+        } else {
+            self.read_digits();
+            if self.ch == Some('.') && self.peek_byte().is_some_and(|byte| byte.is_ascii_digit()) {
+                token_type = TokenType::Float;
+                self.read_char(); // Skip the '.'
+                self.read_digits();
+            }
+            if matches!(self.ch, Some('e' | 'E')) {
+                token_type = TokenType::Float;
+                self.read_char(); // Skip the 'e'
+                if matches!(self.ch, Some('+' | '-')) {
+                    self.read_char();
+                }
+                if self.ch.is_some_and(Self::is_digit) {
+                    self.read_digits();
+                } else {
+                    problem = Some("expected digits in the exponent".to_string());
+                }
+            }
+        }
+
+        let result = self.slice(start, self.position);
+        if let Some(reason) = problem {
+            debug!("Invalid number literal {}: {}", result, reason);
+            self.errors.push(LexError::InvalidNumber {
+                literal: result.to_string(),
+                reason,
+                span: Span::new(start, self.position, line, column),
+            });
+        }
+        trace!("Number read: {}, new position: {}", result, self.position);
+        (token_type, result)
+    }
+
+    // Reads decimal digits and `_` separators
+    fn read_digits(&mut self) {
         while let Some(ch) = self.ch {
-            if Self::is_digit(ch) {
-                trace!("Reading digit: {}", ch as char);
+            if Self::is_digit(ch) || ch == '_' {
+                trace!("Reading digit: {}", ch);
                 self.read_char();
             } else {
-                trace!("Stopping at non-digit: {:?}", self.ch.map(|c| c as char));
+                trace!("Stopping at non-digit: {:?}", self.ch);
                 break;
             }
         }
+    }
 
-        let result = String::from_utf8(self.input[position..self.position].to_vec())
-            .unwrap_or_else(|_| String::new());
-        trace!("Number read: {}, new position: {}", result, self.position);
-        result
+    // Source text between two byte offsets, borrowed unless it holds invalid UTF-8
+    fn slice(&self, start: usize, end: usize) -> Cow<'a, str> {
+        String::from_utf8_lossy(&self.input[start..end])
     }
 
-    fn peek_char(&self) -> Option<char> {
-        if self.read_position >= self.input.len() {
-            None
-        } else {
-            // Try to decode the next char from the byte slice
-            let slice = &self.input[self.read_position..];
-            match std::str::from_utf8(slice) {
-                Ok(s) => s.chars().next(),
-                Err(_) => None,
-            }
+    fn peek_byte(&self) -> Option<u8> {
+        self.input.get(self.read_position).copied()
+    }
+}
+
+// Yields every token up to and including EOF, then stops
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.exhausted {
+            return None;
         }
+        let token = self.next_token();
+        self.exhausted = token.token_type == TokenType::EOF;
+        Some(token)
     }
 }
 
+// Decodes the character starting at byte `position`. Returns None at the end of the
+// input, otherwise the character and its width in bytes, or Err with the number of
+// bytes that do not form valid UTF-8.
+fn decode_char(input: &[u8], position: usize) -> Option<Result<(char, usize), usize>> {
+    let rest = input.get(position..).filter(|rest| !rest.is_empty())?;
+    if rest[0].is_ascii() {
+        return Some(Ok((rest[0] as char, 1)));
+    }
+
+    // A UTF-8 sequence is at most 4 bytes long
+    let window = &rest[..rest.len().min(4)];
+    let valid = match std::str::from_utf8(window) {
+        Ok(text) => text,
+        Err(error) if error.valid_up_to() > 0 => {
+            std::str::from_utf8(&window[..error.valid_up_to()]).ok()?
+        }
+        Err(error) => return Some(Err(error.error_len().unwrap_or(window.len()))),
+    };
+    let ch = valid.chars().next()?;
+    Some(Ok((ch, ch.len_utf8())))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,7 +634,7 @@ let add = fn(x, y) {
 x + y;
 };
 let result = add(five, ten);
-!-/*5;
+!-/ *5;
 5 < 10 > 5;
 if (5 < 10) {
     return true;
@@ -275,6 +643,8 @@ if (5 < 10) {
 }
 10 == 10;
 10 != 9;
+"foobar"
+"foo bar"
 "#;
 
         let tests = vec![
@@ -351,6 +721,8 @@ if (5 < 10) {
             (TokenType::NotEQ, "!="),
             (TokenType::Integer, "9"),
             (TokenType::Semicolon, ";"),
+            (TokenType::String, "foobar"),
+            (TokenType::String, "foo bar"),
             (TokenType::EOF, ""),
         ];
 
@@ -373,4 +745,570 @@ if (5 < 10) {
             );
         }
     }
+
+    #[test]
+    fn test_token_spans() {
+        let input = "let five = 5;\n  five == 10\n";
+
+        let tests = [
+            (TokenType::Let, Span::new(0, 3, 1, 1)),
+            (TokenType::Identifier, Span::new(4, 8, 1, 5)),
+            (TokenType::Assign, Span::new(9, 10, 1, 10)),
+            (TokenType::Integer, Span::new(11, 12, 1, 12)),
+            (TokenType::Semicolon, Span::new(12, 13, 1, 13)),
+            (TokenType::Identifier, Span::new(16, 20, 2, 3)),
+            (TokenType::EQ, Span::new(21, 23, 2, 8)),
+            (TokenType::Integer, Span::new(24, 26, 2, 11)),
+            (TokenType::EOF, Span::new(27, 27, 3, 1)),
+        ];
+
+        let mut lexer = Lexer::new(input.as_bytes());
+
+        for (i, (expected_type, expected_span)) in tests.iter().enumerate() {
+            let tok = lexer.next_token();
+
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.span, expected_span, "tests[{}] - span wrong", i);
+        }
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let input = r#""a\nb" "tab\there" "say \"hi\"" "back\\slash" "\u{41}\u{1F600}" "" "#;
+
+        let expected = [
+            "a\nb",
+            "tab\there",
+            "say \"hi\"",
+            "back\\slash",
+            "A\u{1F600}",
+            "",
+        ];
+
+        let mut lexer = Lexer::new(input.as_bytes());
+        for (i, expected_literal) in expected.iter().enumerate() {
+            let tok = lexer.next_token();
+            assert_eq!(tok.token_type, TokenType::String, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+        }
+        assert_eq!(lexer.next_token().token_type, TokenType::EOF);
+        assert!(lexer.errors().is_empty(), "errors: {:?}", lexer.errors());
+    }
+
+    #[test]
+    fn test_string_span_includes_quotes() {
+        let mut lexer = Lexer::new("x = \"a\\tb\";".as_bytes());
+        lexer.next_token();
+        lexer.next_token();
+
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, TokenType::String);
+        assert_eq!(tok.span, Span::new(4, 10, 1, 5));
+        assert_eq!(lexer.next_token().token_type, TokenType::Semicolon);
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        let input = r#""\q" "\u{110000}" "\u41" "\u{}" "ok""#;
+        let mut lexer = Lexer::new(input.as_bytes());
+
+        // Invalid sequences are kept verbatim and lexing carries on
+        let literals: Vec<String> = (0..5).map(|_| lexer.next_token().literal).collect();
+        assert_eq!(literals, ["\\q", "\\u{110000}", "\\u41", "\\u{}", "ok"]);
+
+        assert_eq!(
+            lexer.errors(),
+            &[
+                LexError::InvalidEscape {
+                    sequence: "\\q".to_string(),
+                    span: Span::new(1, 3, 1, 2),
+                },
+                LexError::InvalidEscape {
+                    sequence: "\\u{110000}".to_string(),
+                    span: Span::new(6, 16, 1, 7),
+                },
+                LexError::InvalidEscape {
+                    sequence: "\\u".to_string(),
+                    span: Span::new(19, 21, 1, 20),
+                },
+                LexError::InvalidEscape {
+                    sequence: "\\u{}".to_string(),
+                    span: Span::new(26, 30, 1, 27),
+                },
+            ]
+        );
+        assert_eq!(
+            lexer.errors()[0].to_string(),
+            "1:2: invalid escape sequence `\\q`"
+        );
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        let mut lexer = Lexer::new("let s = \"abc\n  def".as_bytes());
+        for _ in 0..3 {
+            lexer.next_token();
+        }
+
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, TokenType::String);
+        assert_eq!(tok.literal, "abc\n  def");
+        assert_eq!(lexer.next_token().token_type, TokenType::EOF);
+
+        assert_eq!(
+            lexer.take_errors(),
+            vec![LexError::UnterminatedString {
+                span: Span::new(8, 18, 1, 9),
+            }]
+        );
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_comments_are_skipped() {
+        let input = r#"// leading comment
+let x = 5; // trailing comment
+/* block */ x /* inline */ + /* nested /* inner */ still comment */ 1
+10 / 2 //"#;
+
+        let expected = [
+            (TokenType::Let, "let"),
+            (TokenType::Identifier, "x"),
+            (TokenType::Assign, "="),
+            (TokenType::Integer, "5"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Identifier, "x"),
+            (TokenType::Plus, "+"),
+            (TokenType::Integer, "1"),
+            (TokenType::Integer, "10"),
+            (TokenType::Slash, "/"),
+            (TokenType::Integer, "2"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input.as_bytes());
+        for (i, (expected_type, expected_literal)) in expected.iter().enumerate() {
+            let tok = lexer.next_token();
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+        }
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_comments_as_tokens() {
+        let input = "x // note\n/* a /* b */ c */ y";
+        let mut lexer = Lexer::new(input.as_bytes()).emit_comments(true);
+
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, TokenType::Identifier);
+
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, TokenType::Comment);
+        assert_eq!(tok.literal, "// note");
+        assert_eq!(tok.span, Span::new(2, 9, 1, 3));
+
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, TokenType::Comment);
+        assert_eq!(tok.literal, "/* a /* b */ c */");
+        assert_eq!(tok.span, Span::new(10, 27, 2, 1));
+
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, TokenType::Identifier);
+        assert_eq!(tok.literal, "y");
+        assert_eq!(lexer.next_token().token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        let input = "x /* outer /* inner */ never closed";
+        let mut lexer = Lexer::new(input.as_bytes());
+
+        assert_eq!(lexer.next_token().token_type, TokenType::Identifier);
+        assert_eq!(lexer.next_token().token_type, TokenType::EOF);
+        assert_eq!(
+            lexer.errors(),
+            &[LexError::UnterminatedComment {
+                span: Span::new(2, 35, 1, 3),
+            }]
+        );
+        assert_eq!(
+            lexer.errors()[0].to_string(),
+            "1:3: unterminated block comment"
+        );
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let input = "let مزگت = 1;\nمزگت + _x";
+
+        // Columns count characters, spans count bytes
+        let tests = [
+            (TokenType::Let, "let", Span::new(0, 3, 1, 1)),
+            (TokenType::Identifier, "مزگت", Span::new(4, 12, 1, 5)),
+            (TokenType::Assign, "=", Span::new(13, 14, 1, 10)),
+            (TokenType::Integer, "1", Span::new(15, 16, 1, 12)),
+            (TokenType::Semicolon, ";", Span::new(16, 17, 1, 13)),
+            (TokenType::Identifier, "مزگت", Span::new(18, 26, 2, 1)),
+            (TokenType::Plus, "+", Span::new(27, 28, 2, 6)),
+            (TokenType::Identifier, "_x", Span::new(29, 31, 2, 8)),
+            (TokenType::EOF, "", Span::new(31, 31, 2, 10)),
+        ];
+
+        let mut lexer = Lexer::new(input.as_bytes());
+        for (i, (expected_type, expected_literal, expected_span)) in tests.iter().enumerate() {
+            let tok = lexer.next_token();
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+            assert_eq!(&tok.span, expected_span, "tests[{}] - span wrong", i);
+        }
+        assert!(lexer.errors().is_empty(), "errors: {:?}", lexer.errors());
+    }
+
+    #[test]
+    fn test_identifiers_are_nfc_normalized() {
+        // "café" spelled with a combining acute accent, then precomposed
+        let mut lexer = Lexer::new("cafe\u{301} caf\u{e9}".as_bytes());
+
+        let decomposed = lexer.next_token();
+        let precomposed = lexer.next_token();
+        assert_eq!(decomposed.literal, "caf\u{e9}");
+        assert_eq!(decomposed.literal, precomposed.literal);
+        assert_eq!(decomposed.span, Span::new(0, 6, 1, 1));
+        assert_eq!(precomposed.span, Span::new(7, 12, 1, 7));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let input = b"let \xff x = \"a\x80b\";\n\xe2\x82";
+        let mut lexer = Lexer::new(input);
+
+        let tokens: Vec<(TokenType, String)> = (0..7)
+            .map(|_| {
+                let tok = lexer.next_token();
+                (tok.token_type, tok.literal)
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenType::Let, "let".to_string()),
+                (TokenType::Identifier, "x".to_string()),
+                (TokenType::Assign, "=".to_string()),
+                (TokenType::String, "a\u{FFFD}b".to_string()),
+                (TokenType::Semicolon, ";".to_string()),
+                (TokenType::EOF, "".to_string()),
+                (TokenType::EOF, "".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            lexer.errors(),
+            &[
+                LexError::InvalidUtf8 {
+                    span: Span::new(4, 5, 1, 5),
+                },
+                LexError::InvalidUtf8 {
+                    span: Span::new(12, 13, 1, 13),
+                },
+                LexError::InvalidUtf8 {
+                    span: Span::new(17, 19, 2, 1),
+                },
+            ]
+        );
+        assert_eq!(
+            lexer.errors()[0].to_string(),
+            "1:5: invalid UTF-8 at byte offset 4"
+        );
+    }
+
+    #[test]
+    fn test_identifiers_with_digits() {
+        let mut lexer = Lexer::new("x1 a2b3 _9 1x".as_bytes());
+
+        let tokens: Vec<(TokenType, String)> = (0..6)
+            .map(|_| {
+                let tok = lexer.next_token();
+                (tok.token_type, tok.literal)
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                (TokenType::Identifier, "x1".to_string()),
+                (TokenType::Identifier, "a2b3".to_string()),
+                (TokenType::Identifier, "_9".to_string()),
+                (TokenType::Integer, "1".to_string()),
+                (TokenType::Identifier, "x".to_string()),
+                (TokenType::EOF, "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numeric_literals() {
+        let input = "42 1_000 0xFF 0o17 0b1010 3.14 1e10 2.5E-3 6e+2 7.x";
+
+        let expected = [
+            (TokenType::Integer, "42"),
+            (TokenType::Integer, "1_000"),
+            (TokenType::Integer, "0xFF"),
+            (TokenType::Integer, "0o17"),
+            (TokenType::Integer, "0b1010"),
+            (TokenType::Float, "3.14"),
+            (TokenType::Float, "1e10"),
+            (TokenType::Float, "2.5E-3"),
+            (TokenType::Float, "6e+2"),
+            // A dot not followed by a digit is not part of the number
+            (TokenType::Integer, "7"),
+            (TokenType::Dot, "."),
+        ];
+
+        let mut lexer = Lexer::new(input.as_bytes());
+        for (i, (expected_type, expected_literal)) in expected.iter().enumerate() {
+            let tok = lexer.next_token();
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+        }
+        assert!(lexer.errors().is_empty(), "errors: {:?}", lexer.errors());
+    }
+
+    #[test]
+    fn test_invalid_numbers() {
+        let mut lexer = Lexer::new("0x 1e 0b102 2e+;".as_bytes());
+
+        let literals: Vec<String> = (0..5).map(|_| lexer.next_token().literal).collect();
+        assert_eq!(literals, ["0x", "1e", "0b102", "2e+", ";"]);
+
+        assert_eq!(
+            lexer.errors(),
+            &[
+                LexError::InvalidNumber {
+                    literal: "0x".to_string(),
+                    reason: "expected hexadecimal digits after the prefix".to_string(),
+                    span: Span::new(0, 2, 1, 1),
+                },
+                LexError::InvalidNumber {
+                    literal: "1e".to_string(),
+                    reason: "expected digits in the exponent".to_string(),
+                    span: Span::new(3, 5, 1, 4),
+                },
+                LexError::InvalidNumber {
+                    literal: "0b102".to_string(),
+                    reason: "invalid digit `2` in binary literal".to_string(),
+                    span: Span::new(6, 11, 1, 7),
+                },
+                LexError::InvalidNumber {
+                    literal: "2e+".to_string(),
+                    reason: "expected digits in the exponent".to_string(),
+                    span: Span::new(12, 15, 1, 13),
+                },
+            ]
+        );
+        assert_eq!(
+            lexer.errors()[0].to_string(),
+            "1:1: invalid number literal `0x`: expected hexadecimal digits after the prefix"
+        );
+    }
+
+    #[test]
+    fn test_operators_maximal_munch() {
+        let input = "a <= b >= c && d || e % f ** g += -= *= /= -> => . : [ ] <<= !== ***";
+
+        let expected = [
+            (TokenType::Identifier, "a"),
+            (TokenType::LTEQ, "<="),
+            (TokenType::Identifier, "b"),
+            (TokenType::GTEQ, ">="),
+            (TokenType::Identifier, "c"),
+            (TokenType::And, "&&"),
+            (TokenType::Identifier, "d"),
+            (TokenType::Or, "||"),
+            (TokenType::Identifier, "e"),
+            (TokenType::Percent, "%"),
+            (TokenType::Identifier, "f"),
+            (TokenType::Power, "**"),
+            (TokenType::Identifier, "g"),
+            (TokenType::PlusAssign, "+="),
+            (TokenType::MinusAssign, "-="),
+            (TokenType::AsteriskAssign, "*="),
+            (TokenType::SlashAssign, "/="),
+            (TokenType::Arrow, "->"),
+            (TokenType::FatArrow, "=>"),
+            (TokenType::Dot, "."),
+            (TokenType::Colon, ":"),
+            (TokenType::LeftBracket, "["),
+            (TokenType::RightBracket, "]"),
+            // Longest match first, then whatever is left over
+            (TokenType::LT, "<"),
+            (TokenType::LTEQ, "<="),
+            (TokenType::NotEQ, "!="),
+            (TokenType::Assign, "="),
+            (TokenType::Power, "**"),
+            (TokenType::Asterisk, "*"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lexer = Lexer::new(input.as_bytes());
+        for (i, (expected_type, expected_literal)) in expected.iter().enumerate() {
+            let tok = lexer.next_token();
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+        }
+    }
+
+    #[test]
+    fn test_unexpected_characters() {
+        let mut lexer = Lexer::new("a & b | c\n  @#".as_bytes());
+
+        // They are reported and skipped rather than turned into tokens
+        let types: Vec<TokenType> = (0..4).map(|_| lexer.next_token().token_type).collect();
+        assert_eq!(
+            types,
+            [
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::EOF,
+            ]
+        );
+
+        assert_eq!(
+            lexer.errors(),
+            &[
+                LexError::UnexpectedCharacter {
+                    ch: '&',
+                    span: Span::new(2, 3, 1, 3),
+                },
+                LexError::UnexpectedCharacter {
+                    ch: '|',
+                    span: Span::new(6, 7, 1, 7),
+                },
+                LexError::UnexpectedCharacter {
+                    ch: '@',
+                    span: Span::new(12, 13, 2, 3),
+                },
+                LexError::UnexpectedCharacter {
+                    ch: '#',
+                    span: Span::new(13, 14, 2, 4),
+                },
+            ]
+        );
+        assert_eq!(
+            lexer.errors()[0].to_string(),
+            "1:3: unexpected character `&`"
+        );
+    }
+
+    #[test]
+    fn test_iterator_ends_after_eof() {
+        let lexer = Lexer::new("let x = 1;".as_bytes());
+
+        let types: Vec<TokenType> = lexer.map(|tok| tok.token_type).collect();
+        assert_eq!(
+            types,
+            [
+                TokenType::Let,
+                TokenType::Identifier,
+                TokenType::Assign,
+                TokenType::Integer,
+                TokenType::Semicolon,
+                TokenType::EOF,
+            ]
+        );
+
+        let mut empty = Lexer::new(b"");
+        assert_eq!(empty.next().map(|tok| tok.token_type), Some(TokenType::EOF));
+        assert_eq!(empty.next(), None);
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = Lexer::tokenize("a + 1").unwrap();
+        let literals: Vec<&str> = tokens.iter().map(|tok| tok.literal.as_str()).collect();
+        assert_eq!(literals, ["a", "+", "1", ""]);
+        assert_eq!(tokens[2].span, Span::new(4, 5, 1, 5));
+
+        assert_eq!(
+            Lexer::tokenize("x = \"open"),
+            Err(LexError::UnterminatedString {
+                span: Span::new(4, 9, 1, 5),
+            })
+        );
+    }
+
+    #[test]
+    fn test_borrowed_tokens() {
+        let input = "let x1 = \"plain\" + \"esc\\n\" // note\n cafe\u{301} 0xFF;";
+        let mut lexer = Lexer::new(input.as_bytes()).emit_comments(true);
+
+        let expected = [
+            (TokenType::Let, "let", true),
+            (TokenType::Identifier, "x1", true),
+            (TokenType::Assign, "=", true),
+            (TokenType::String, "plain", true),
+            (TokenType::Plus, "+", true),
+            // Escapes and normalization have to build a new string
+            (TokenType::String, "esc\n", false),
+            (TokenType::Comment, "// note", true),
+            (TokenType::Identifier, "caf\u{e9}", false),
+            (TokenType::Integer, "0xFF", true),
+            (TokenType::Semicolon, ";", true),
+            (TokenType::EOF, "", true),
+        ];
+
+        for (i, (expected_type, expected_literal, borrowed)) in expected.iter().enumerate() {
+            let tok = lexer.next_borrowed_token();
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+            assert_eq!(
+                matches!(tok.literal, Cow::Borrowed(_)),
+                *borrowed,
+                "tests[{}] - wrong ownership",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_localized_keywords() {
+        let keywords = KeywordTable::persian();
+        let input = "بگذار عدد = تابع(x) { اگر (x) { برگردان درست; } وگرنه { غلط } }; let";
+        let mut lexer = Lexer::with_keywords(input.as_bytes(), &keywords);
+
+        let expected = [
+            (TokenType::Let, "بگذار"),
+            (TokenType::Identifier, "عدد"),
+            (TokenType::Assign, "="),
+            (TokenType::Function, "تابع"),
+            (TokenType::LeftParen, "("),
+            (TokenType::Identifier, "x"),
+            (TokenType::RightParen, ")"),
+            (TokenType::LeftBrace, "{"),
+            (TokenType::IF, "اگر"),
+            (TokenType::LeftParen, "("),
+            (TokenType::Identifier, "x"),
+            (TokenType::RightParen, ")"),
+            (TokenType::LeftBrace, "{"),
+            (TokenType::Return, "برگردان"),
+            (TokenType::True, "درست"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::RightBrace, "}"),
+            (TokenType::ELSE, "وگرنه"),
+            (TokenType::LeftBrace, "{"),
+            (TokenType::False, "غلط"),
+            (TokenType::RightBrace, "}"),
+            (TokenType::RightBrace, "}"),
+            (TokenType::Semicolon, ";"),
+            // Only the table's keywords are recognized
+            (TokenType::Identifier, "let"),
+            (TokenType::EOF, ""),
+        ];
+
+        for (i, (expected_type, expected_literal)) in expected.iter().enumerate() {
+            let tok = lexer.next_token();
+            assert_eq!(&tok.token_type, expected_type, "tests[{}]", i);
+            assert_eq!(&tok.literal, expected_literal, "tests[{}]", i);
+        }
+    }
 }
@@ -1,7 +1,17 @@
+mod error;
+mod keywords;
 mod lexer;
 mod logger;
+mod lookahead;
+mod span;
+mod stream;
 mod token;
 
+pub use error::LexError;
+pub use keywords::KeywordTable;
 pub use lexer::Lexer;
 pub use logger::{init_logger, init_test_logger};
-pub use token::{Token, TokenType};
+pub use lookahead::Lookahead;
+pub use span::Span;
+pub use stream::StreamLexer;
+pub use token::{BorrowedToken, Token, TokenType};
@@ -0,0 +1,90 @@
+use crate::error::LexError;
+use crate::lexer::Lexer;
+use crate::token::Token;
+use std::collections::VecDeque;
+
+// Wraps a lexer so callers can look any number of tokens ahead without consuming
+// them. Lexical errors stay with the token that caused them and are only handed
+// out once that token has been consumed, so looking ahead does not reorder them.
+#[derive(Debug)]
+pub struct Lookahead<'a> {
+    lexer: Lexer<'a>,
+    buffer: VecDeque<(Token, Vec<LexError>)>,
+    errors: Vec<LexError>, // Errors of tokens already returned by next_token
+}
+
+impl<'a> Lookahead<'a> {
+    pub fn new(lexer: Lexer<'a>) -> Self {
+        Self {
+            lexer,
+            buffer: VecDeque::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // The token `n` places ahead of the next one; peek_nth(0) is what next_token
+    // returns next. Past the end of the input this is always EOF.
+    pub fn peek_nth(&mut self, n: usize) -> &Token {
+        while self.buffer.len() <= n {
+            let token = self.lexer.next_token();
+            let errors = self.lexer.take_errors();
+            self.buffer.push_back((token, errors));
+        }
+        &self.buffer[n].0
+    }
+
+    pub fn peek(&mut self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    pub fn next_token(&mut self) -> Token {
+        self.peek_nth(0);
+        let (token, errors) = self.buffer.pop_front().expect("peek_nth filled the buffer");
+        self.errors.extend(errors);
+        token
+    }
+
+    // Hands over the errors of the tokens consumed so far
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Span;
+    use crate::token::TokenType;
+
+    #[test]
+    fn test_peek_nth() {
+        let mut tokens = Lookahead::new(Lexer::new("a b c".as_bytes()));
+
+        assert_eq!(tokens.peek_nth(2).literal, "c");
+        assert_eq!(tokens.peek_nth(0).literal, "a");
+        assert_eq!(tokens.peek_nth(5).token_type, TokenType::EOF);
+
+        let literals: Vec<String> = (0..4).map(|_| tokens.next_token().literal).collect();
+        assert_eq!(literals, ["a", "b", "c", ""]);
+        assert_eq!(tokens.peek().token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn test_errors_follow_their_token() {
+        let mut tokens = Lookahead::new(Lexer::new("a \"\\q\" b".as_bytes()));
+
+        // Looking past the bad string does not report it yet
+        tokens.peek_nth(2);
+        assert_eq!(tokens.next_token().literal, "a");
+        assert!(tokens.take_errors().is_empty());
+
+        assert_eq!(tokens.next_token().token_type, TokenType::String);
+        assert_eq!(
+            tokens.take_errors(),
+            vec![LexError::InvalidEscape {
+                sequence: "\\q".to_string(),
+                span: Span::new(3, 5, 1, 4),
+            }]
+        );
+    }
+}
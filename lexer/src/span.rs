@@ -0,0 +1,53 @@
+use std::fmt;
+
+// Location of a piece of source text: byte offsets [start, end) into the input,
+// plus the 1-based line and column where it begins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    // Covers everything from the start of `self` to the end of `other`
+    pub fn to(self, other: Span) -> Self {
+        Span {
+            end: other.end.max(self.end),
+            ..self
+        }
+    }
+
+    // The same span in a text that starts `offset` bytes later
+    pub(crate) fn shift(self, offset: usize) -> Self {
+        Span {
+            start: self.start + offset,
+            end: self.end + offset,
+            ..self
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
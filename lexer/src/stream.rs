@@ -0,0 +1,247 @@
+use crate::error::LexError;
+use crate::keywords::KeywordTable;
+use crate::lexer::Lexer;
+use crate::token::{Token, TokenType};
+use log::debug;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+// Smallest read made when the buffer runs dry
+const CHUNK_SIZE: usize = 8 * 1024;
+
+// How far past a token the lexer may look to decide where it ends: a whole UTF-8
+// character, which also covers the second character of operators like `<=`
+const LOOKAHEAD: usize = 4;
+
+// Lexes source pulled from any reader instead of a slice held in memory. Input
+// sits in a ring buffer only until the token it belongs to has been returned, so
+// memory use follows the longest token rather than the size of the input.
+//
+// Tokens and errors are exactly those of `Lexer` on the whole input, spans included.
+pub struct StreamLexer<R> {
+    reader: R,
+    buffer: VecDeque<u8>,
+    chunk: Vec<u8>, // Scratch space for reads
+    reader_done: bool,
+    offset: usize, // Byte offset of the buffer's front in the whole input
+    line: usize,
+    column: usize,
+    errors: Vec<LexError>,
+    emit_comments: bool,
+    exhausted: bool, // The iterator has already handed out the EOF token
+    keywords: KeywordTable,
+}
+
+impl<R: Read> StreamLexer<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: VecDeque::with_capacity(CHUNK_SIZE),
+            chunk: Vec::new(),
+            reader_done: false,
+            offset: 0,
+            line: 1,
+            column: 1,
+            errors: Vec::new(),
+            emit_comments: false,
+            exhausted: false,
+            keywords: KeywordTable::english(),
+        }
+    }
+
+    // See Lexer::with_keywords
+    pub fn keywords(mut self, keywords: KeywordTable) -> Self {
+        self.keywords = keywords;
+        self
+    }
+
+    // See Lexer::emit_comments
+    pub fn emit_comments(mut self, emit: bool) -> Self {
+        self.emit_comments = emit;
+        self
+    }
+
+    pub fn next_token(&mut self) -> io::Result<Token> {
+        loop {
+            let input = self.buffer.make_contiguous();
+            let mut lexer = Lexer::resume(input, self.line, self.column, &self.keywords)
+                .emit_comments(self.emit_comments);
+            let token = lexer.next_token();
+            let (consumed, line, column) = lexer.location();
+
+            // A token ending this close to the end of the buffer might continue past
+            // it, and so might whitespace or a comment before EOF, or a character cut
+            // in half; lex it again with more input
+            if input.len() - consumed < LOOKAHEAD && !self.reader_done {
+                self.fill()?;
+                continue;
+            }
+
+            // Errors past the token, such as an invalid byte the lexer stepped onto
+            // while finishing it, are reported again when lexing resumes there
+            let offset = self.offset;
+            self.errors.extend(
+                lexer
+                    .take_errors()
+                    .into_iter()
+                    .filter(|error| error.span().start < consumed)
+                    .map(|mut error| {
+                        let span = error.span_mut();
+                        *span = span.shift(offset);
+                        error
+                    }),
+            );
+            self.buffer.drain(..consumed);
+            self.offset += consumed;
+            self.line = line;
+            self.column = column;
+
+            let span = token.span.shift(offset);
+            return Ok(token.with_span(span));
+        }
+    }
+
+    // Reads once from the reader, asking for at least as much as is already buffered
+    // so a token spanning many reads is only lexed a logarithmic number of times
+    fn fill(&mut self) -> io::Result<()> {
+        let size = self.buffer.len().max(CHUNK_SIZE);
+        if self.chunk.len() < size {
+            self.chunk.resize(size, 0);
+        }
+        loop {
+            match self.reader.read(&mut self.chunk[..size]) {
+                Ok(0) => {
+                    debug!(
+                        "Reader exhausted at byte offset {}",
+                        self.offset + self.buffer.len()
+                    );
+                    self.reader_done = true;
+                    return Ok(());
+                }
+                Ok(read) => {
+                    self.buffer.extend(&self.chunk[..read]);
+                    return Ok(());
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
+    // Hands over the errors collected so far, leaving the lexer's list empty
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+// Yields every token up to and including EOF, then stops. A read error is yielded
+// once and also ends the iteration.
+impl<R: Read> Iterator for StreamLexer<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<io::Result<Token>> {
+        if self.exhausted {
+            return None;
+        }
+        let token = self.next_token();
+        self.exhausted = match &token {
+            Ok(token) => token.token_type == TokenType::EOF,
+            Err(_) => true,
+        };
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hands out one byte per read, the worst case for tokens split across reads
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&byte, rest)) if !buf.is_empty() => {
+                    buf[0] = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn assert_same_as_slice_lexer(input: &[u8]) {
+        let mut lexer = Lexer::new(input).emit_comments(true);
+        let expected: Vec<Token> = lexer.by_ref().collect();
+
+        let mut stream = StreamLexer::new(OneByteReader(input)).emit_comments(true);
+        let tokens: Vec<Token> = stream.by_ref().map(Result::unwrap).collect();
+
+        assert_eq!(
+            tokens,
+            expected,
+            "input: {:?}",
+            String::from_utf8_lossy(input)
+        );
+        assert_eq!(stream.errors(), lexer.errors());
+
+        // A reader that hands out everything at once
+        let mut stream = StreamLexer::new(input).emit_comments(true);
+        let tokens: Vec<Token> = stream.by_ref().map(Result::unwrap).collect();
+        assert_eq!(tokens, expected);
+        assert_eq!(stream.errors(), lexer.errors());
+    }
+
+    #[test]
+    fn test_one_byte_reads_match_slice_lexer() {
+        let inputs: [&[u8]; 9] = [
+            b"let five = 5;\nlet add = fn(x, y) { x + y; };\nadd(five, 10) <= 15 != false",
+            "let مزگت = \"سلام\\n\";\n  cafe\u{301} ** 0xFF_FF".as_bytes(),
+            b"a /* outer /* inner */ still */ b // trailing\n3.5e-2 / 2",
+            b"\"esc \\u{1F600} \\q\" 0x 1e",
+            b"let \xff x = \"a\x80b\";\n\xe2\x82",
+            b"x = \"never closed",
+            // Invalid bytes right after a token
+            b"let\xffx",
+            b"\"abc\"\xff\xfe 1",
+            b"  \n\n ",
+        ];
+        for input in inputs {
+            assert_same_as_slice_lexer(input);
+        }
+    }
+
+    #[test]
+    fn test_large_input() {
+        let input = "let value_1 = \"some text\" + 12345;\n".repeat(200);
+        assert_same_as_slice_lexer(input.as_bytes());
+
+        let tokens: Vec<Token> = StreamLexer::new(input.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(tokens.len(), 200 * 7 + 1);
+        assert_eq!(tokens[7].span.line, 2);
+    }
+
+    #[test]
+    fn test_read_errors_are_returned() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("connection reset"))
+            }
+        }
+
+        let mut stream = StreamLexer::new(FailingReader);
+        let error = stream.next().unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "connection reset");
+        assert!(stream.next().is_none());
+    }
+}
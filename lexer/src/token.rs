@@ -1,12 +1,17 @@
+use crate::keywords::english_keywords;
+use crate::span::Span;
+use std::borrow::Cow;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TokenType {
-    Illegal,
     EOF,
 
     Integer,
+    Float,
     Identifier,
+    String,
+    Comment,
 
     Assign,
     Plus,
@@ -14,17 +19,35 @@ pub enum TokenType {
     Bang,
     Asterisk,
     Slash,
+    Percent,
+    Power,
+
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
 
     LT,
     GT,
+    LTEQ,
+    GTEQ,
+
+    And,
+    Or,
 
     Comma,
     Semicolon,
+    Dot,
+    Colon,
+    Arrow,
+    FatArrow,
 
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
 
     Function,
     Let,
@@ -39,26 +62,17 @@ pub enum TokenType {
 }
 
 impl TokenType {
+    // Keyword lookup with the English keyword table; see KeywordTable for others
     pub fn lookup_ident(ident: &str) -> Self {
-        match ident {
-            "fn" => TokenType::Function,
-            "let" => TokenType::Let,
-            "true" => TokenType::True,
-            "false" => TokenType::False,
-            "if" => TokenType::IF,
-            "else" => TokenType::ELSE,
-            "return" => TokenType::Return,
-            "==" => TokenType::EQ,
-            "!=" => TokenType::NotEQ,
-            _ => TokenType::Identifier,
-        }
+        english_keywords().lookup(ident)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Token {
     pub token_type: TokenType,
     pub literal: String,
+    pub span: Span,
 }
 
 impl Token {
@@ -66,6 +80,7 @@ impl Token {
         Token {
             token_type,
             literal,
+            span: Span::default(),
         }
     }
 
@@ -73,6 +88,42 @@ impl Token {
         Self {
             token_type,
             literal: ch.to_string(),
+            span: Span::default(),
+        }
+    }
+
+    pub fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
+}
+
+// A token whose literal borrows from the lexer's input where it can, so lexing does
+// not allocate for the common case. Convert with into_owned to keep it around longer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowedToken<'a> {
+    pub token_type: TokenType,
+    pub literal: Cow<'a, str>,
+    pub span: Span,
+}
+
+impl<'a> BorrowedToken<'a> {
+    pub fn new(token_type: TokenType, literal: impl Into<Cow<'a, str>>) -> Self {
+        BorrowedToken {
+            token_type,
+            literal: literal.into(),
+            span: Span::default(),
+        }
+    }
+
+    pub fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
+
+    pub fn into_owned(self) -> Token {
+        Token {
+            token_type: self.token_type,
+            literal: self.literal.into_owned(),
+            span: self.span,
         }
     }
 }
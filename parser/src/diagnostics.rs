@@ -0,0 +1,346 @@
+use crate::ParseError;
+use lexer::{LexError, Span, TokenType};
+use std::fmt::Write;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Plain,
+    Ansi,
+}
+
+// A message about a span of source code, rendered rustc-style:
+//
+// error: expected Identifier, got Assign instead
+//  --> 1:5
+//   |
+// 1 | let = 5;
+//   |     ^ expected Identifier
+//   |
+//   = help: ...
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub label: Option<String>, // Printed next to the underline
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            span,
+            label: None,
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    // `source` must be the same input the lexer was created with
+    pub fn render(&self, source: &[u8], mode: RenderMode) -> String {
+        let paint = |color: &str, text: &str| match mode {
+            RenderMode::Plain => text.to_string(),
+            RenderMode::Ansi => format!("{}{}{}", color, text, RESET),
+        };
+
+        let source = String::from_utf8_lossy(source);
+        let (line_text, prefix, marked) = snippet(&source, self.span);
+        let line_number = self.span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let bar = paint(BLUE, "|");
+
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "{}{}",
+            paint(self.severity.color(), self.severity.as_str()),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+        let _ = writeln!(output, "{}{} {}", gutter, paint(BLUE, "-->"), self.span);
+        let _ = writeln!(output, "{} {}", gutter, bar);
+        let _ = writeln!(
+            output,
+            "{} {} {}",
+            paint(BLUE, &line_number),
+            bar,
+            line_text
+        );
+
+        // Mirror tabs so the underline lines up with the source line above it
+        let padding: String = prefix
+            .chars()
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underline = "^".repeat(marked.chars().count().max(1));
+        let mut marker = paint(self.severity.color(), &underline);
+        if let Some(label) = &self.label {
+            marker.push(' ');
+            marker.push_str(&paint(self.severity.color(), label));
+        }
+        let _ = writeln!(output, "{} {} {}{}", gutter, bar, padding, marker);
+
+        if !self.notes.is_empty() || !self.help.is_empty() {
+            let _ = writeln!(output, "{} {}", gutter, bar);
+        }
+        for note in &self.notes {
+            let _ = writeln!(output, "{} = {}: {}", gutter, paint(BOLD, "note"), note);
+        }
+        for help in &self.help {
+            let _ = writeln!(output, "{} = {}: {}", gutter, paint(CYAN, "help"), help);
+        }
+
+        output
+    }
+}
+
+// Splits the source line containing `span` into (whole line, text before the span, text under it).
+// Spans running past the end of the line are cut at the line break.
+fn snippet(source: &str, span: Span) -> (&str, &str, &str) {
+    let start = floor_char_boundary(source, span.start.min(source.len()));
+    let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |index| start + index);
+    let end = floor_char_boundary(source, span.end.clamp(start, line_end));
+
+    let line = source[line_start..line_end].trim_end_matches('\r');
+    let marked_end = end.min(line_start + line.len());
+    (
+        line,
+        &source[line_start..start],
+        &source[start.min(marked_end)..marked_end],
+    )
+}
+
+fn floor_char_boundary(source: &str, mut index: usize) -> usize {
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let diagnostic = Diagnostic::error(error.message(), error.span());
+
+        match error {
+            ParseError::UnexpectedToken { expected, found } => {
+                let expected = expected
+                    .iter()
+                    .map(|token_type| token_type.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" or ");
+                let diagnostic = diagnostic.with_label(format!("expected {}", expected));
+                if found.token_type == TokenType::EOF {
+                    diagnostic.with_note("the input ended here")
+                } else {
+                    diagnostic
+                }
+            }
+            ParseError::NoPrefixParser { token } => {
+                diagnostic.with_label(format!("{} cannot start an expression", token.token_type))
+            }
+            ParseError::InvalidLiteral { token, reason } => {
+                let diagnostic = diagnostic.with_label(reason.clone());
+                if token.token_type == TokenType::Integer {
+                    diagnostic.with_help(format!("integer literals must not exceed {}", i64::MAX))
+                } else {
+                    diagnostic
+                }
+            }
+            ParseError::UnterminatedBlock { open, .. } => diagnostic
+                .with_label(format!("expected {}", TokenType::RightBrace))
+                .with_note(format!("the block was opened at {}", open.span)),
+            ParseError::Lexical(error) => Diagnostic::from(error),
+        }
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        let diagnostic = Diagnostic::error(error.message(), error.span());
+
+        match error {
+            LexError::UnexpectedCharacter { .. } => {
+                diagnostic.with_label("not valid in Mazgamet source")
+            }
+            LexError::UnterminatedString { .. } => diagnostic
+                .with_label("this string is never closed")
+                .with_help("add a closing `\"`"),
+            LexError::UnterminatedComment { .. } => diagnostic
+                .with_label("this comment is never closed")
+                .with_note("block comments nest, so every `/*` needs its own `*/`"),
+            LexError::InvalidEscape { .. } => diagnostic
+                .with_label("unknown escape")
+                .with_help("valid escapes are \\n, \\t, \\\", \\\\ and \\u{...}"),
+            LexError::InvalidNumber { .. } => diagnostic
+                .with_label("malformed number")
+                .with_help("numbers look like 42, 1_000, 0xFF, 0o17, 0b1010, 3.14 or 2.5e-3"),
+            LexError::InvalidUtf8 { .. } => diagnostic
+                .with_label("not valid UTF-8")
+                .with_note("Mazgamet source files must be encoded as UTF-8"),
+        }
+    }
+}
+
+// Renders every error, separated by blank lines, in the order they were reported
+pub fn render_errors(errors: &[ParseError], source: &[u8], mode: RenderMode) -> String {
+    errors
+        .iter()
+        .map(|error| Diagnostic::from(error).render(source, mode))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+    use lexer::Lexer;
+
+    fn parse_errors(input: &str) -> Vec<ParseError> {
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+        parser.errors().to_vec()
+    }
+
+    #[test]
+    fn test_render_unexpected_token() {
+        let input = "let x = 1;\nlet = 5;";
+        let errors = parse_errors(input);
+
+        let rendered = Diagnostic::from(&errors[0]).render(input.as_bytes(), RenderMode::Plain);
+        assert_eq!(
+            rendered,
+            "error: expected Identifier, got Assign instead\n \
+             --> 2:5\n  \
+             |\n\
+             2 | let = 5;\n  \
+             |     ^ expected Identifier\n"
+        );
+    }
+
+    #[test]
+    fn test_render_notes_and_help() {
+        let input = "if (x) {\n  99999999999999999999";
+        let errors = parse_errors(input);
+
+        assert_eq!(
+            render_errors(&errors, input.as_bytes(), RenderMode::Plain),
+            "error: invalid literal 99999999999999999999: number too large to fit in target type\n \
+             --> 2:3\n  \
+             |\n\
+             2 |   99999999999999999999\n  \
+             |   ^^^^^^^^^^^^^^^^^^^^ number too large to fit in target type\n  \
+             |\n  \
+             = help: integer literals must not exceed 9223372036854775807\n\
+             \n\
+             error: expected RightBrace to close block, got EOF instead\n \
+             --> 2:23\n  \
+             |\n\
+             2 |   99999999999999999999\n  \
+             |                       ^ expected RightBrace\n  \
+             |\n  \
+             = note: the block was opened at 1:8\n"
+        );
+    }
+
+    #[test]
+    fn test_render_unexpected_character() {
+        let input = "\tlet a = 1 @ 2;";
+        let errors = parse_errors(input);
+
+        let rendered = Diagnostic::from(&errors[0]).render(input.as_bytes(), RenderMode::Plain);
+        assert_eq!(
+            rendered,
+            "error: unexpected character `@`\n \
+             --> 1:12\n  \
+             |\n\
+             1 | \tlet a = 1 @ 2;\n  \
+             | \t          ^ not valid in Mazgamet source\n"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let input = "let = 5;";
+        let errors = parse_errors(input);
+
+        let rendered = Diagnostic::from(&errors[0]).render(input.as_bytes(), RenderMode::Ansi);
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: expected Identifier"));
+        assert!(rendered.contains("\x1b[1;31m^\x1b[0m \x1b[1;31mexpected Identifier\x1b[0m"));
+
+        let plain: String =
+            Diagnostic::from(&errors[0]).render(input.as_bytes(), RenderMode::Plain);
+        assert!(!plain.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_render_lexical_error() {
+        let input = "let s = \"a\\qb\";";
+        let errors = parse_errors(input);
+
+        assert_eq!(
+            render_errors(&errors, input.as_bytes(), RenderMode::Plain),
+            "error: invalid escape sequence `\\q`\n \
+             --> 1:11\n  \
+             |\n\
+             1 | let s = \"a\\qb\";\n  \
+             |           ^^ unknown escape\n  \
+             |\n  \
+             = help: valid escapes are \\n, \\t, \\\", \\\\ and \\u{...}\n"
+        );
+    }
+}
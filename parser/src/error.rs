@@ -0,0 +1,116 @@
+use lexer::{LexError, Span, Token, TokenType};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    // A token other than the one(s) the grammar requires at this point
+    UnexpectedToken {
+        expected: Vec<TokenType>,
+        found: Token,
+    },
+    // The token cannot start an expression
+    NoPrefixParser {
+        token: Token,
+    },
+    // The token has the right type but its literal cannot be represented, e.g. integer overflow
+    InvalidLiteral {
+        token: Token,
+        reason: String,
+    },
+    // Input ended before the `}` matching `open`
+    UnterminatedBlock {
+        open: Token,
+        found: Token,
+    },
+    // The lexer could not make sense of the source text
+    Lexical(LexError),
+}
+
+impl ParseError {
+    // The token the parser was looking at when it gave up; lexical errors have none
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            ParseError::UnexpectedToken { found, .. } => Some(found),
+            ParseError::NoPrefixParser { token } => Some(token),
+            ParseError::InvalidLiteral { token, .. } => Some(token),
+            ParseError::UnterminatedBlock { found, .. } => Some(found),
+            ParseError::Lexical(_) => None,
+        }
+    }
+
+    // The token types that would have been accepted instead, if the error is about one
+    pub fn expected(&self) -> &[TokenType] {
+        match self {
+            ParseError::UnexpectedToken { expected, .. } => expected,
+            ParseError::UnterminatedBlock { .. } => &[TokenType::RightBrace],
+            ParseError::NoPrefixParser { .. }
+            | ParseError::InvalidLiteral { .. }
+            | ParseError::Lexical(_) => &[],
+        }
+    }
+
+    // Whether the lexer rather than the parser found this error
+    pub fn is_lexical(&self) -> bool {
+        matches!(self, ParseError::Lexical(_))
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::Lexical(error) => error.span(),
+            _ => self.token().map(|token| token.span).unwrap_or_default(),
+        }
+    }
+
+    // The error text without its location
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken { expected, found } => {
+                let expected = expected
+                    .iter()
+                    .map(|token_type| token_type.to_string())
+                    .collect::<Vec<String>>();
+                let expected = match expected.as_slice() {
+                    [single] => single.clone(),
+                    _ => format!("one of {}", expected.join(", ")),
+                };
+                format!("expected {}, got {} instead", expected, found.token_type)
+            }
+            ParseError::NoPrefixParser { token } => {
+                format!("no prefix parse function for {} found", token.token_type)
+            }
+            ParseError::InvalidLiteral { token, reason } => {
+                format!("invalid literal {}: {}", token.literal, reason)
+            }
+            ParseError::UnterminatedBlock { found, .. } => {
+                format!(
+                    "expected {} to close block, got {} instead",
+                    TokenType::RightBrace,
+                    found.token_type
+                )
+            }
+            ParseError::Lexical(error) => error.message(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span(), self.message())
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Lexical(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<LexError> for ParseError {
+    fn from(error: LexError) -> Self {
+        ParseError::Lexical(error)
+    }
+}
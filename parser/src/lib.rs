@@ -1,14 +1,23 @@
+pub mod diagnostics;
+mod error;
+
 use std::collections::HashMap;
+use std::num::ParseIntError;
+use std::sync::Arc;
 
 use ast::{
-    Expression, ExpressionStatement, Identifier, LetStatement, Program, ReturnStatement, Statement,
-}; // Added ExpressionStatement
-use lexer::{Lexer, Token, TokenType};
+    ArrayLiteral, BlockStatement, Boolean, CallExpression, Expression, ExpressionStatement,
+    FunctionLiteral, HashLiteral, Identifier, IfExpression, IndexExpression, InfixExpression,
+    IntegerLiteral, LetStatement, Node, PrefixExpression, Program, ReturnStatement, Statement,
+    StringLiteral,
+};
+use lexer::{Lexer, Lookahead, Span, Token, TokenType};
+
+pub use error::ParseError;
 
-type Errors = Vec<String>;
+type Errors = Vec<ParseError>;
 
 // Define Precedence levels for Pratt parsing
-#[allow(dead_code)]
 #[derive(PartialOrd, PartialEq, Debug, Copy, Clone)]
 enum Precedence {
     Lowest,
@@ -18,17 +27,46 @@ enum Precedence {
     Product,     // *
     Prefix,      // -X or !X
     Call,        // myFunction(X)
+    Index,       // array[index]
+}
+
+// Binding power of each infix operator; anything else never continues an expression
+fn precedence_of(token_type: &TokenType) -> Precedence {
+    match token_type {
+        TokenType::EQ | TokenType::NotEQ => Precedence::Equals,
+        TokenType::LT | TokenType::GT => Precedence::LessGreater,
+        TokenType::Plus | TokenType::Minus => Precedence::Sum,
+        TokenType::Asterisk | TokenType::Slash => Precedence::Product,
+        TokenType::LeftParen => Precedence::Call,
+        TokenType::LeftBracket => Precedence::Index,
+        _ => Precedence::Lowest,
+    }
+}
+
+// Value of an integer literal as the lexer wrote it: decimal, or hex/octal/binary
+// after a 0x/0o/0b prefix, with any `_` separators
+fn parse_integer(literal: &str) -> Result<i64, ParseIntError> {
+    let digits = literal.replace('_', "");
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, digits.as_str()),
+    };
+    i64::from_str_radix(digits, radix)
 }
 
 // Adjusted PrefixParseFn and InfixParseFn to match your existing definition style
-pub type PrefixParseFn = fn(&mut Parser) -> Option<Box<dyn Expression>>;
-pub type InfixParseFn = fn(&mut Parser, Box<dyn Expression>) -> Option<Box<dyn Expression>>;
+pub type PrefixParseFn = fn(&mut Parser) -> Option<Expression>;
+pub type InfixParseFn = fn(&mut Parser, Expression) -> Option<Expression>;
 
 pub struct Parser<'a> {
-    lexer: Lexer<'a>,
+    tokens: Lookahead<'a>,
     current_token: Token,
     peek_token: Token,
+    previous_span: Span, // Span of the last consumed token, where a node ends
     errors: Errors,
+    lexical_errors: usize, // errors[..lexical_errors] came from the lexer
 
     prefix_parse_fns: HashMap<TokenType, PrefixParseFn>,
     infix_parse_fns: HashMap<TokenType, InfixParseFn>,
@@ -37,10 +75,12 @@ pub struct Parser<'a> {
 impl<'a> Parser<'a> {
     pub fn new(lexer: Lexer<'a>) -> Self {
         let mut parser = Self {
-            lexer,
+            tokens: Lookahead::new(lexer),
             current_token: Token::new(TokenType::EOF, String::new()),
             peek_token: Token::new(TokenType::EOF, String::new()),
+            previous_span: Span::default(),
             errors: Vec::new(),
+            lexical_errors: 0,
             prefix_parse_fns: HashMap::new(),
             infix_parse_fns: HashMap::new(),
         };
@@ -50,6 +90,33 @@ impl<'a> Parser<'a> {
             TokenType::Identifier,
             Self::parse_identifier_as_expression_prefix,
         );
+        parser.register_prefix(TokenType::Integer, Self::parse_integer_literal);
+        parser.register_prefix(TokenType::String, Self::parse_string_literal);
+        parser.register_prefix(TokenType::True, Self::parse_boolean);
+        parser.register_prefix(TokenType::False, Self::parse_boolean);
+        parser.register_prefix(TokenType::Bang, Self::parse_prefix_expression);
+        parser.register_prefix(TokenType::Minus, Self::parse_prefix_expression);
+        parser.register_prefix(TokenType::IF, Self::parse_if_expression);
+        parser.register_prefix(TokenType::LeftParen, Self::parse_grouped_expression);
+        parser.register_prefix(TokenType::Function, Self::parse_function_literal);
+        parser.register_prefix(TokenType::LeftBracket, Self::parse_array_literal);
+        parser.register_prefix(TokenType::LeftBrace, Self::parse_hash_literal);
+
+        // Register infix parsing functions
+        for token_type in [
+            TokenType::Plus,
+            TokenType::Minus,
+            TokenType::Asterisk,
+            TokenType::Slash,
+            TokenType::LT,
+            TokenType::GT,
+            TokenType::EQ,
+            TokenType::NotEQ,
+        ] {
+            parser.register_infix(token_type, Self::parse_infix_expression);
+        }
+        parser.register_infix(TokenType::LeftParen, Self::parse_call_expression);
+        parser.register_infix(TokenType::LeftBracket, Self::parse_index_expression);
 
         // Read two tokens to initialize cur_token and peek_token
         parser.next_token();
@@ -59,19 +126,236 @@ impl<'a> Parser<'a> {
     }
 
     // Prefix parsing function for identifiers
-    fn parse_identifier_as_expression_prefix(parser: &mut Parser) -> Option<Box<dyn Expression>> {
-        Some(Box::new(Identifier {
+    fn parse_identifier_as_expression_prefix(parser: &mut Parser) -> Option<Expression> {
+        let identifier = Identifier {
+            token: parser.current_token.clone(),
+            span: parser.current_token.span,
+            value: parser.current_token.literal.clone(),
+        };
+        parser.next_token(); // Consume the identifier
+        Some(Expression::Identifier(identifier))
+    }
+
+    fn parse_integer_literal(parser: &mut Parser) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        parser.next_token(); // Consume the integer
+
+        // Literals too large for an i64 are a user error, not a reason to panic
+        match parse_integer(&token.literal) {
+            Ok(value) => Some(Expression::IntegerLiteral(IntegerLiteral {
+                span: token.span,
+                token,
+                value,
+            })),
+            // Malformed literals such as `0x` were already reported by the lexer
+            Err(_) if parser.has_lexical_error_at(token.span) => None,
+            Err(err) => {
+                parser.errors.push(ParseError::InvalidLiteral {
+                    reason: err.to_string(),
+                    token,
+                });
+                None
+            }
+        }
+    }
+
+    fn has_lexical_error_at(&self, span: Span) -> bool {
+        self.lexical_errors()
+            .iter()
+            .any(|error| error.span() == span)
+    }
+
+    fn parse_string_literal(parser: &mut Parser) -> Option<Expression> {
+        let string = StringLiteral {
             token: parser.current_token.clone(),
+            span: parser.current_token.span,
             value: parser.current_token.literal.clone(),
+        };
+        parser.next_token(); // Consume the string
+        Some(Expression::StringLiteral(string))
+    }
+
+    fn parse_boolean(parser: &mut Parser) -> Option<Expression> {
+        let boolean = Boolean {
+            token: parser.current_token.clone(),
+            span: parser.current_token.span,
+            value: parser.current_token_is(TokenType::True),
+        };
+        parser.next_token(); // Consume 'true' or 'false'
+        Some(Expression::Boolean(boolean))
+    }
+
+    // Prefix parsing function for unary operators; current_token is the operator
+    fn parse_prefix_expression(parser: &mut Parser) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        parser.next_token(); // Consume the operator
+
+        let right = parser.parse_expression(Precedence::Prefix)?;
+
+        Some(Expression::Prefix(PrefixExpression {
+            span: token.span.to(parser.previous_span),
+            operator: token.literal.clone(),
+            token,
+            right: Box::new(right),
+        }))
+    }
+
+    // Parses `(expression)`; the parentheses only affect grouping
+    fn parse_grouped_expression(parser: &mut Parser) -> Option<Expression> {
+        parser.next_token(); // Consume '('
+
+        let expression = parser.parse_expression(Precedence::Lowest)?;
+
+        if !parser.expect_current(TokenType::RightParen) {
+            return None;
+        }
+
+        Some(expression)
+    }
+
+    // Parses `fn(a, b) { ... }`; current_token is 'fn'
+    fn parse_function_literal(parser: &mut Parser) -> Option<Expression> {
+        let token = parser.current_token.clone();
+
+        if !parser.expect_peek(TokenType::LeftParen) {
+            return None;
+        }
+
+        let parameters = parser.parse_function_parameters()?;
+        let body = parser.parse_block_statement()?;
+
+        Some(Expression::FunctionLiteral(Arc::new(FunctionLiteral {
+            span: token.span.to(parser.previous_span),
+            token,
+            parameters,
+            body,
+        })))
+    }
+
+    // Parses `(a, b)`; current_token is '('
+    fn parse_function_parameters(&mut self) -> Option<Vec<Identifier>> {
+        let mut parameters = Vec::new();
+        self.next_token(); // Consume '('
+
+        if self.current_token_is(TokenType::RightParen) {
+            self.next_token(); // Consume ')'
+            return Some(parameters);
+        }
+
+        loop {
+            let token = self.current_token.clone();
+            if !self.expect_current(TokenType::Identifier) {
+                return None;
+            }
+            parameters.push(Identifier {
+                span: token.span,
+                value: token.literal.clone(),
+                token,
+            });
+
+            if !self.current_token_is(TokenType::Comma) {
+                break;
+            }
+            self.next_token(); // Consume ','
+        }
+
+        if !self.expect_current(TokenType::RightParen) {
+            return None;
+        }
+
+        Some(parameters)
+    }
+
+    // Parses `if (condition) { ... } else { ... }`; current_token is 'if'
+    fn parse_if_expression(parser: &mut Parser) -> Option<Expression> {
+        let token = parser.current_token.clone();
+
+        if !parser.expect_peek(TokenType::LeftParen) {
+            return None;
+        }
+        parser.next_token(); // Consume '('
+
+        let condition = parser.parse_expression(Precedence::Lowest)?;
+
+        if !parser.expect_current(TokenType::RightParen) {
+            return None;
+        }
+
+        let consequence = parser.parse_block_statement()?;
+
+        let alternative = if parser.current_token_is(TokenType::ELSE) {
+            parser.next_token(); // Consume 'else'
+            Some(parser.parse_block_statement()?)
+        } else {
+            None
+        };
+
+        Some(Expression::If(Box::new(IfExpression {
+            span: token.span.to(parser.previous_span),
+            token,
+            condition: Box::new(condition),
+            consequence,
+            alternative,
+        })))
+    }
+
+    // Infix parsing function for binary operators; current_token is the operator
+    fn parse_infix_expression(parser: &mut Parser, left: Expression) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        let precedence = parser.current_precedence();
+        parser.next_token(); // Consume the operator
+
+        let right = parser.parse_expression(precedence)?;
+
+        Some(Expression::Infix(InfixExpression {
+            span: left.span().to(parser.previous_span),
+            operator: token.literal.clone(),
+            token,
+            left: Box::new(left),
+            right: Box::new(right),
         }))
     }
 
     pub fn next_token(&mut self) {
+        self.previous_span = self.current_token.span;
         self.current_token = std::mem::replace(
             &mut self.peek_token,
             Token::new(TokenType::EOF, String::new()), // Default token
         );
-        self.peek_token = self.lexer.next_token();
+        self.peek_token = self.tokens.next_token();
+        // Comments only reach the parser if the lexer was asked to emit them
+        while self.peek_token.token_type == TokenType::Comment {
+            self.peek_token = self.tokens.next_token();
+        }
+
+        // Malformed literals still come through as tokens; report what the lexer found wrong.
+        // Lexical errors go ahead of syntax errors, which they often explain.
+        let lexical_errors = self.tokens.take_errors();
+        let count = lexical_errors.len();
+        self.errors.splice(
+            self.lexical_errors..self.lexical_errors,
+            lexical_errors.into_iter().map(ParseError::Lexical),
+        );
+        self.lexical_errors += count;
+    }
+
+    // The token `n` places after peek_token, skipping comments like next_token does;
+    // peek_nth_token(0) is peek_token itself
+    pub fn peek_nth_token(&mut self, n: usize) -> &Token {
+        if n == 0 {
+            return &self.peek_token;
+        }
+        let (mut index, mut seen) = (0, 0);
+        loop {
+            if self.tokens.peek_nth(index).token_type != TokenType::Comment {
+                seen += 1;
+                if seen == n {
+                    break;
+                }
+            }
+            index += 1;
+        }
+        self.tokens.peek_nth(index)
     }
 
     pub fn parse_program(&mut self) -> Program {
@@ -79,29 +363,72 @@ impl<'a> Parser<'a> {
             statements: Vec::new(),
         };
         while !self.current_token_is(TokenType::EOF) {
-            if let Some(statement) = self.parse_statement() {
-                program.statements.push(statement);
+            let statement_start = self.current_token.span.start;
+            match self.parse_statement() {
+                Some(statement) => program.statements.push(statement),
+                None => self.synchronize(statement_start),
             }
-            // self.next_token(); // parse_statement should consume its tokens including the last one (e.g. semicolon or end of expression)
-            // If parse_statement doesn't advance to the next statement's beginning, this is needed.
-            // Let's assume parse_statement advances correctly.
         }
         program
     }
 
-    pub fn errors(&self) -> &Errors {
+    // Like parse_program, but only hands out the Program when it parsed cleanly
+    pub fn try_parse_program(&mut self) -> Result<Program, Errors> {
+        let program = self.parse_program();
+        if self.errors.is_empty() {
+            Ok(program)
+        } else {
+            self.lexical_errors = 0;
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // Every error so far: those found by the lexer first, then the syntax errors
+    pub fn errors(&self) -> &[ParseError] {
         &self.errors
     }
 
+    pub fn lexical_errors(&self) -> &[ParseError] {
+        &self.errors[..self.lexical_errors]
+    }
+
+    pub fn syntax_errors(&self) -> &[ParseError] {
+        &self.errors[self.lexical_errors..]
+    }
+
     fn peek_error(&mut self, expected_token: &TokenType) {
-        let message = format!(
-            "expected next token to be {}, got {} instead",
-            expected_token, self.peek_token.token_type
-        );
-        self.errors.push(message);
+        self.errors.push(ParseError::UnexpectedToken {
+            expected: vec![expected_token.clone()],
+            found: self.peek_token.clone(),
+        });
+    }
+
+    // Panic-mode recovery after a statement failed to parse: skip tokens until a
+    // point where a fresh statement can start, so one mistake yields one error.
+    // Stops after a `;`, or before `let`, `return` or a `}` closing the enclosing
+    // block. Those stop tokens only count once the parser has moved past the
+    // start of the failed statement, so every call makes progress.
+    fn synchronize(&mut self, statement_start: usize) {
+        let mut depth = 0; // Braces opened while skipping
+        loop {
+            let moved = self.current_token.span.start > statement_start;
+            match self.current_token.token_type {
+                TokenType::EOF => return,
+                TokenType::Semicolon if depth == 0 => {
+                    self.next_token(); // Consume ';'
+                    return;
+                }
+                TokenType::Let | TokenType::Return if depth == 0 && moved => return,
+                TokenType::RightBrace if depth == 0 && moved => return,
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth > 0 => depth -= 1,
+                _ => {}
+            }
+            self.next_token();
+        }
     }
 
-    fn parse_statement(&mut self) -> Option<Box<dyn Statement>> {
+    fn parse_statement(&mut self) -> Option<Statement> {
         match self.current_token.token_type {
             TokenType::Let => self.parse_let_statement(),
             TokenType::Return => self.parse_return_statement(),
@@ -109,27 +436,33 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_return_statement(&mut self) -> Option<Box<dyn Statement>> {
+    fn parse_return_statement(&mut self) -> Option<Statement> {
         let token = self.current_token.clone();
         self.next_token(); // Consume 'return'
 
-        // TODO: Parse the expression for the return value
-        // For now, skip to semicolon
-        while !self.current_token_is(TokenType::Semicolon) && !self.current_token_is(TokenType::EOF)
+        // A bare `return;` (or `return` closing a block or the input) carries no value
+        let return_value = if self.current_token_is(TokenType::Semicolon)
+            || self.current_token_is(TokenType::RightBrace)
+            || self.current_token_is(TokenType::EOF)
         {
-            self.next_token();
-        }
+            None
+        } else {
+            Some(self.parse_expression(Precedence::Lowest)?)
+        };
+
+        // The trailing semicolon is optional
         if self.current_token_is(TokenType::Semicolon) {
             self.next_token(); // Consume ';'
         }
 
-        Some(Box::new(ReturnStatement {
+        Some(Statement::Return(ReturnStatement {
+            span: token.span.to(self.previous_span),
             token,
-            return_value: None, // Placeholder
+            return_value,
         }))
     }
 
-    fn parse_let_statement(&mut self) -> Option<Box<dyn Statement>> {
+    fn parse_let_statement(&mut self) -> Option<Statement> {
         let token = self.current_token.clone(); // The 'let' token
 
         if !self.expect_peek(TokenType::Identifier) {
@@ -139,6 +472,7 @@ impl<'a> Parser<'a> {
         let name = Identifier {
             // This is the identifier AST node
             token: self.current_token.clone(), // The identifier token
+            span: self.current_token.span,
             value: self.current_token.literal.clone(),
         };
 
@@ -147,52 +481,180 @@ impl<'a> Parser<'a> {
         }
         self.next_token(); // Consume '='
 
-        // TODO: Parse the expression for the value
-        // For now, skip to semicolon
-        while !self.current_token_is(TokenType::Semicolon) && !self.current_token_is(TokenType::EOF)
-        {
-            self.next_token();
-        }
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        // The trailing semicolon is optional
         if self.current_token_is(TokenType::Semicolon) {
             self.next_token(); // Consume ';'
         }
 
-        Some(Box::new(LetStatement {
+        Some(Statement::Let(LetStatement {
+            span: token.span.to(self.previous_span),
             token, // 'let' token
-            name: Some(Box::new(name)),
-            value: None, // Placeholder
+            name,
+            value,
+        }))
+    }
+
+    // Infix parsing function for calls; current_token is the '(' after the callee
+    fn parse_call_expression(parser: &mut Parser, function: Expression) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        let arguments = parser.parse_expression_list(TokenType::RightParen)?;
+
+        Some(Expression::Call(CallExpression {
+            span: function.span().to(parser.previous_span),
+            token,
+            function: Box::new(function),
+            arguments,
+        }))
+    }
+
+    // Parses comma-separated expressions such as `(1, 2 * 3)` or `[a, b]`;
+    // current_token is the opening delimiter and `end` the closing one
+    fn parse_expression_list(&mut self, end: TokenType) -> Option<Vec<Expression>> {
+        let mut elements = Vec::new();
+        self.next_token(); // Consume the opening delimiter
+
+        if self.current_token_is(end.clone()) {
+            self.next_token(); // Consume the closing delimiter
+            return Some(elements);
+        }
+
+        elements.push(self.parse_expression(Precedence::Lowest)?);
+        while self.current_token_is(TokenType::Comma) {
+            self.next_token(); // Consume ','
+            elements.push(self.parse_expression(Precedence::Lowest)?);
+        }
+
+        if !self.expect_current(end) {
+            return None;
+        }
+
+        Some(elements)
+    }
+
+    // Prefix parsing function for `[1, 2, 3]`; current_token is '['
+    fn parse_array_literal(parser: &mut Parser) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        let elements = parser.parse_expression_list(TokenType::RightBracket)?;
+
+        Some(Expression::ArrayLiteral(ArrayLiteral {
+            span: token.span.to(parser.previous_span),
+            token,
+            elements,
+        }))
+    }
+
+    // Prefix parsing function for `{"a": 1, b: 2}`; current_token is '{'. Blocks
+    // are only parsed where the grammar requires one (after `if`, `else` and a
+    // function's parameters), so a '{' where an expression starts is a hash.
+    fn parse_hash_literal(parser: &mut Parser) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        parser.next_token(); // Consume '{'
+
+        let mut pairs = Vec::new();
+        while !parser.current_token_is(TokenType::RightBrace) {
+            if parser.current_token_is(TokenType::EOF) {
+                parser.errors.push(ParseError::UnterminatedBlock {
+                    open: token,
+                    found: parser.current_token.clone(),
+                });
+                return None;
+            }
+            if !pairs.is_empty() && !parser.expect_current(TokenType::Comma) {
+                return None;
+            }
+            let key = parser.parse_expression(Precedence::Lowest)?;
+            if !parser.expect_current(TokenType::Colon) {
+                return None;
+            }
+            let value = parser.parse_expression(Precedence::Lowest)?;
+            pairs.push((key, value));
+        }
+        parser.next_token(); // Consume '}'
+
+        Some(Expression::HashLiteral(HashLiteral {
+            span: token.span.to(parser.previous_span),
+            token,
+            pairs,
+        }))
+    }
+
+    // Infix parsing function for `left[index]`; current_token is '['
+    fn parse_index_expression(parser: &mut Parser, left: Expression) -> Option<Expression> {
+        let token = parser.current_token.clone();
+        parser.next_token(); // Consume '['
+        let index = parser.parse_expression(Precedence::Lowest)?;
+        if !parser.expect_current(TokenType::RightBracket) {
+            return None;
+        }
+
+        Some(Expression::Index(IndexExpression {
+            span: left.span().to(parser.previous_span),
+            token,
+            left: Box::new(left),
+            index: Box::new(index),
         }))
     }
 
-    fn parse_expression_statement(&mut self) -> Option<Box<dyn Statement>> {
+    // Parses `{ ... }`; current_token must be the opening brace
+    fn parse_block_statement(&mut self) -> Option<BlockStatement> {
+        let token = self.current_token.clone();
+        if !self.expect_current(TokenType::LeftBrace) {
+            return None;
+        }
+
+        let mut statements = Vec::new();
+        while !self.current_token_is(TokenType::RightBrace) {
+            if self.current_token_is(TokenType::EOF) {
+                self.errors.push(ParseError::UnterminatedBlock {
+                    open: token,
+                    found: self.current_token.clone(),
+                });
+                return None;
+            }
+
+            let statement_start = self.current_token.span.start;
+            match self.parse_statement() {
+                Some(statement) => statements.push(statement),
+                None => self.synchronize(statement_start),
+            }
+        }
+        self.next_token(); // Consume '}'
+
+        Some(BlockStatement {
+            span: token.span.to(self.previous_span),
+            token,
+            statements,
+        })
+    }
+
+    fn parse_expression_statement(&mut self) -> Option<Statement> {
         let stmt_token = self.current_token.clone(); // Token that starts the expression (e.g. "foobar")
 
-        let expression = self.parse_expression(Precedence::Lowest);
+        // On failure the error was already pushed by parse_expression or one of its callees
+        let expression = self.parse_expression(Precedence::Lowest)?;
 
         // After parse_expression, self.current_token is the token AFTER the expression.
         // For "foobar;", current_token should now be TokenType::Semicolon.
 
-        if let Some(expr) = expression {
-            // expression is Some(Identifier("foobar"))
-            let stmt = ExpressionStatement {
-                token: stmt_token,
-                expression: Some(expr),
-            };
-
-            // If the expression statement is followed by a semicolon, consume it.
-            // This is common for expression statements in languages like JavaScript or C.
-            if self.current_token_is(TokenType::Semicolon) {
-                self.next_token(); // Consume the semicolon
-            }
-            Some(Box::new(stmt))
-        } else {
-            // Error already pushed by parse_expression or one of its callees
-            None
+        // If the expression statement is followed by a semicolon, consume it.
+        // This is common for expression statements in languages like JavaScript or C.
+        if self.current_token_is(TokenType::Semicolon) {
+            self.next_token(); // Consume the semicolon
         }
+
+        Some(Statement::Expression(ExpressionStatement {
+            span: stmt_token.span.to(self.previous_span),
+            token: stmt_token,
+            expression,
+        }))
     }
 
-    // Core of the Pratt parser for expressions
-    fn parse_expression(&mut self, _precedence: Precedence) -> Option<Box<dyn Expression>> {
+    // Core of the Pratt parser for expressions.
+    // Every parse function consumes the tokens it uses, so once it returns
+    // self.current_token is the first token AFTER the parsed expression.
+    fn parse_expression(&mut self, precedence: Precedence) -> Option<Expression> {
         // '.cloned()' is used because `prefix_fn` is `fn(&mut Parser)`,
         // which means it might try to borrow `self` mutably again if not careful.
         // Cloning the function pointer itself is cheap.
@@ -201,35 +663,37 @@ impl<'a> Parser<'a> {
             .get(&self.current_token.token_type)
             .cloned();
 
-        let expression_node = match prefix_fn_option {
-            Some(p_fn) => {
-                // The prefix function (e.g., parse_identifier_as_expression_prefix)
-                // creates an AST node based on the current_token.
-                // It DOES NOT advance the token itself.
-                p_fn(self)
-            }
+        let mut left = match prefix_fn_option {
+            Some(p_fn) => p_fn(self),
             None => {
-                let msg = format!(
-                    "no prefix parse function for token type {} found. Token: {:?}",
-                    self.current_token.token_type, self.current_token
-                );
-                self.errors.push(msg);
+                self.errors.push(ParseError::NoPrefixParser {
+                    token: self.current_token.clone(),
+                });
                 return None;
             }
         }?; // If p_fn returns None (error during its execution), propagate it.
 
-        // After the prefix function has successfully created the initial part of the expression (the "left" node),
-        // parse_expression advances the token. This consumes the token(s) used by the prefix function.
-        self.next_token();
+        // Keep folding `left` into infix expressions for as long as the operator
+        // under the cursor binds tighter than the caller's precedence.
+        // Semicolons and EOF sit at Precedence::Lowest, so they stop the loop.
+        while precedence < self.current_precedence() {
+            let infix_fn = match self
+                .infix_parse_fns
+                .get(&self.current_token.token_type)
+                .cloned()
+            {
+                Some(i_fn) => i_fn,
+                None => return Some(left),
+            };
+
+            left = infix_fn(self, left)?;
+        }
 
-        // TODO: Implement infix operator parsing loop here
-        // while !_precedence_is_semicolon && precedence < self.peek_precedence() {
-        //    ... look up infix_parse_fn ...
-        //    ... self.next_token() to consume operator ...
-        //    ... expression_node = infix_fn(self, expression_node) ...
-        // }
+        Some(left)
+    }
 
-        Some(expression_node)
+    fn current_precedence(&self) -> Precedence {
+        precedence_of(&self.current_token.token_type)
     }
 
     fn current_token_is(&self, token_type: TokenType) -> bool {
@@ -240,6 +704,20 @@ impl<'a> Parser<'a> {
         self.peek_token.token_type == *token_type
     }
 
+    // Like expect_peek, but checks the current token and consumes it on success
+    fn expect_current(&mut self, token_type: TokenType) -> bool {
+        if self.current_token_is(token_type.clone()) {
+            self.next_token();
+            true
+        } else {
+            self.errors.push(ParseError::UnexpectedToken {
+                expected: vec![token_type],
+                found: self.current_token.clone(),
+            });
+            false
+        }
+    }
+
     fn expect_peek(&mut self, token_type: TokenType) -> bool {
         if self.peek_token_is(&token_type) {
             self.next_token();
@@ -254,7 +732,6 @@ impl<'a> Parser<'a> {
         self.prefix_parse_fns.insert(token_type, func);
     }
 
-    #[allow(dead_code)] // Kept for future use
     pub fn register_infix(&mut self, token_type: TokenType, func: InfixParseFn) {
         self.infix_parse_fns.insert(token_type, func);
     }
@@ -263,9 +740,7 @@ impl<'a> Parser<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ast::Node; // For token_literal
-                   // use lexer::Lexer; // Already imported at the top of parser/src/lib.rs
-                   // use ast::{Statement, LetStatement, Identifier, ReturnStatement, ExpressionStatement, Expression}; // Partially imported at top
+    use lexer::{KeywordTable, LexError};
 
     fn check_parser_errors(parser: &Parser) {
         let errors = parser.errors();
@@ -279,71 +754,94 @@ mod tests {
         panic!("Parser errors encountered");
     }
 
+    fn parse(input: &str) -> Program {
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        check_parser_errors(&parser);
+
+        program
+    }
+
+    fn first_error(input: &str) -> Option<String> {
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        parser.errors().first().map(ParseError::message)
+    }
+
+    // Unwraps a program made of exactly one expression statement
+    fn single_expression(program: &Program) -> &Expression {
+        assert_eq!(
+            program.statements.len(),
+            1,
+            "program.statements does not contain 1 statement. got={}",
+            program.statements.len()
+        );
+
+        match &program.statements[0] {
+            Statement::Expression(expr_stmt) => &expr_stmt.expression,
+            other => panic!(
+                "program.statements[0] is not ast::ExpressionStatement. got={:?}",
+                other
+            ),
+        }
+    }
+
     #[test]
     fn test_let_statements() {
         let input = r#"
             let x = 5;
             let y = 10;
             let foobar = 121212;
+            let مزگت = 1;
+            let naïve_x = 2;
             "#;
-        let lexer = Lexer::new(input.as_bytes());
-        let mut parser = Parser::new(lexer);
-        let program = parser.parse_program();
-
-        check_parser_errors(&parser);
+        let program = parse(input);
 
         assert_eq!(
             program.statements.len(),
-            3,
-            "Expected 3 statements, got {}",
+            5,
+            "Expected 5 statements, got {}",
             program.statements.len()
         );
 
-        let tests = ["x", "y", "foobar"];
+        let tests = ["x", "y", "foobar", "مزگت", "naïve_x"];
 
         for (i, &expected_ident) in tests.iter().enumerate() {
             let statement = &program.statements[i];
-            test_let_statement(expected_ident, statement.as_ref());
+            test_let_statement(expected_ident, statement);
         }
     }
 
-    fn test_let_statement(expected_ident: &str, statement: &dyn Statement) {
+    fn test_let_statement(expected_ident: &str, statement: &Statement) {
         assert_eq!(
             statement.token_literal(),
             "let",
             "Statement token literal should be 'let'"
         );
 
-        let let_stmt = match statement.as_any().downcast_ref::<LetStatement>() {
-            Some(s) => s,
-            #[allow(non_snake_case)]
-            None => panic!(
-                "Statement is not a LetStatement. Got: {:?}",
-                statement.as_any()
-            ),
+        let let_stmt = match statement {
+            Statement::Let(s) => s,
+            other => panic!("Statement is not a LetStatement. Got: {:?}", other),
         };
 
-        match let_stmt.name {
-            Some(ref ident_box) => {
-                // ident_box is Box<Identifier>
-                let ident = ident_box.as_ref(); // Get &Identifier from Box<Identifier>
-                assert_eq!(
-                    ident.value, expected_ident,
-                    "Identifier value should be '{}', got '{}'",
-                    expected_ident, ident.value
-                );
+        let ident = &let_stmt.name;
+        assert_eq!(
+            ident.value, expected_ident,
+            "Identifier value should be '{}', got '{}'",
+            expected_ident, ident.value
+        );
 
-                assert_eq!(
-                    ident.token_literal(),
-                    expected_ident,
-                    "Identifier token literal should be '{}', got '{}'",
-                    expected_ident,
-                    ident.token_literal()
-                );
-            }
-            #[allow(non_snake_case)]
-            None => panic!("Let statement's name is None"),
-        }
+        assert_eq!(
+            ident.token_literal(),
+            expected_ident,
+            "Identifier token literal should be '{}', got '{}'",
+            expected_ident,
+            ident.token_literal()
+        );
     }
 
     #[test]
@@ -353,12 +851,7 @@ mod tests {
             return 10;
             return 888888;
             "#;
-
-        let lexer = Lexer::new(input.as_bytes());
-        let mut parser = Parser::new(lexer);
-        let program = parser.parse_program();
-
-        check_parser_errors(&parser);
+        let program = parse(input);
 
         assert_eq!(
             program.statements.len(),
@@ -367,14 +860,11 @@ mod tests {
             program.statements.len()
         );
 
-        for statement in &program.statements {
-            let return_statement = match statement.as_any().downcast_ref::<ReturnStatement>() {
-                Some(s) => s,
-                #[allow(non_snake_case)]
-                None => panic!(
-                    "Statement is not a ReturnStatement. Got: {:?}",
-                    statement.as_any()
-                ),
+        let expected_values = [5, 10, 888888];
+        for (statement, expected) in program.statements.iter().zip(expected_values) {
+            let return_statement = match statement {
+                Statement::Return(s) => s,
+                other => panic!("Statement is not a ReturnStatement. Got: {:?}", other),
             };
 
             assert_eq!(
@@ -382,62 +872,903 @@ mod tests {
                 "return",
                 "Return statement token literal should be 'return'"
             );
+
+            match &return_statement.return_value {
+                Some(Expression::IntegerLiteral(literal)) => assert_eq!(literal.value, expected),
+                other => panic!("return value is not an integer. got={:?}", other),
+            }
         }
     }
 
     #[test]
-    fn test_identifier_expression() {
-        let input = "foobar;";
-        let lexer = Lexer::new(input.as_bytes());
-        let mut parser = Parser::new(lexer);
-        let program = parser.parse_program();
+    fn test_let_and_return_values() {
+        let tests = [
+            ("let x = 5;", "let x = 5;"),
+            ("let y = true;", "let y = true;"),
+            ("let foobar = y;", "let foobar = y;"),
+            ("let z = a + b * c;", "let z = (a + (b * c));"),
+            ("let x = 5", "let x = 5;"),
+            ("let x = 1 let y = 2", "let x = 1;let y = 2;"),
+            ("return 5;", "return 5;"),
+            ("return -x * y;", "return ((-x) * y);"),
+            ("return a == b", "return (a == b);"),
+            ("return;", "return;"),
+            ("return", "return;"),
+        ];
 
-        check_parser_errors(&parser);
+        for (input, expected) in tests {
+            assert_eq!(parse(input).to_string(), expected, "input: {}", input);
+        }
+    }
 
-        assert_eq!(
-            program.statements.len(),
-            1,
-            "program.statements does not contain 1 statement. got={}",
-            program.statements.len()
+    #[test]
+    fn test_identifier_expression() {
+        let program = parse("foobar;");
+
+        match single_expression(&program) {
+            Expression::Identifier(ident) => {
+                assert_eq!(
+                    ident.value, "foobar",
+                    "ident.value not {}. got={}",
+                    "foobar", ident.value
+                );
+                assert_eq!(
+                    ident.token_literal(),
+                    "foobar",
+                    "ident.token_literal not {}. got={}",
+                    "foobar",
+                    ident.token_literal()
+                );
+            }
+            other => panic!("stmt.expression is not ast::Identifier. got={:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parsing_infix_expressions() {
+        let tests = [
+            ("a + b;", "a", "+", "b"),
+            ("a - b;", "a", "-", "b"),
+            ("a * b;", "a", "*", "b"),
+            ("a / b;", "a", "/", "b"),
+            ("a > b;", "a", ">", "b"),
+            ("a < b;", "a", "<", "b"),
+            ("a == b;", "a", "==", "b"),
+            ("a != b;", "a", "!=", "b"),
+        ];
+
+        for (input, expected_left, expected_operator, expected_right) in tests {
+            let program = parse(input);
+
+            match single_expression(&program) {
+                Expression::Infix(infix) => {
+                    assert_eq!(infix.operator, expected_operator);
+                    assert_eq!(infix.left.to_string(), expected_left);
+                    assert_eq!(infix.right.to_string(), expected_right);
+                }
+                other => panic!(
+                    "stmt.expression is not ast::InfixExpression. got={:?}",
+                    other
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence_parsing() {
+        let tests = [
+            ("a + b + c", "((a + b) + c)"),
+            ("a + b - c", "((a + b) - c)"),
+            ("a * b * c", "((a * b) * c)"),
+            ("a * b / c", "((a * b) / c)"),
+            ("a + b / c", "(a + (b / c))"),
+            ("a + b * c", "(a + (b * c))"),
+            ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
+            ("a + b; c * d", "(a + b)(c * d)"),
+            ("a > b == c < d", "((a > b) == (c < d))"),
+            ("a < b != c > d", "((a < b) != (c > d))"),
+            ("a + b * c == d * e + f", "((a + (b * c)) == ((d * e) + f))"),
+            (
+                "a * [1, 2, 3, 4][b * c] * d",
+                "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+            ),
+            (
+                "add(a * b[2], b[1], 2 * [1, 2][1])",
+                "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(parse(input).to_string(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_integer_literal_expression() {
+        let program = parse("5;");
+
+        match single_expression(&program) {
+            Expression::IntegerLiteral(literal) => {
+                assert_eq!(literal.value, 5);
+                assert_eq!(literal.token_literal(), "5");
+            }
+            other => panic!(
+                "stmt.expression is not ast::IntegerLiteral. got={:?}",
+                other
+            ),
+        }
+    }
+
+    #[test]
+    fn test_integer_literal_forms() {
+        let tests = [
+            ("1_000_000;", 1_000_000),
+            ("0xFF;", 255),
+            ("0Xdead_beef;", 0xdead_beef),
+            ("0o17;", 15),
+            ("0b1010_1010;", 170),
+            ("0x7FFF_FFFF_FFFF_FFFF;", i64::MAX),
+        ];
+
+        for (input, expected) in tests {
+            let program = parse(input);
+            match single_expression(&program) {
+                Expression::IntegerLiteral(literal) => {
+                    assert_eq!(literal.value, expected, "input: {}", input);
+                    assert_eq!(literal.token_literal(), &input[..input.len() - 1]);
+                }
+                other => panic!("expected an IntegerLiteral for {}, got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_integer_reported_once() {
+        let lexer = Lexer::new("let x = 0x;".as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        assert_eq!(
+            parser.errors(),
+            &[ParseError::Lexical(LexError::InvalidNumber {
+                literal: "0x".to_string(),
+                reason: "expected hexadecimal digits after the prefix".to_string(),
+                span: Span::new(8, 10, 1, 9),
+            })]
         );
+    }
 
-        let stmt_trait_object = &program.statements.first().unwrap(); // This is &Box<dyn Statement>
-        let stmt_as_any = stmt_trait_object.as_any(); // This is &dyn Any
+    #[test]
+    fn test_integer_literal_overflow() {
+        let input = "99999999999999999999;";
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
 
-        match stmt_as_any.downcast_ref::<ExpressionStatement>() {
-            Some(expr_stmt) => {
-                assert!(
-                    expr_stmt.expression.is_some(),
-                    "ExpressionStatement's expression is None"
-                );
+        match parser.errors() {
+            [ParseError::InvalidLiteral { token, .. }] => {
+                assert_eq!(token.literal, "99999999999999999999");
+                assert_eq!(token.span, Span::new(0, 20, 1, 1));
+            }
+            other => panic!("expected a single InvalidLiteral error, got {:?}", other),
+        }
+        assert_eq!(
+            parser.errors()[0].to_string(),
+            "1:1: invalid literal 99999999999999999999: number too large to fit in target type"
+        );
+    }
 
-                // Get the inner Box<dyn Expression> and then downcast it
-                let dyn_expr = expr_stmt.expression.as_ref().unwrap(); // This is &Box<dyn Expression>
-                match dyn_expr.as_any().downcast_ref::<Identifier>() {
-                    Some(ident) => {
-                        assert_eq!(
-                            ident.value, "foobar",
-                            "ident.value not {}. got={}",
-                            "foobar", ident.value
-                        );
-                        assert_eq!(
-                            ident.token_literal(),
-                            "foobar",
-                            "ident.token_literal not {}. got={}",
-                            "foobar",
-                            ident.token_literal()
-                        );
-                    }
-                    None => panic!(
-                        "stmt.expression is not ast::Identifier. got={:?}",
-                        expr_stmt.token_literal()
-                    ),
+    #[test]
+    fn test_boolean_expression() {
+        let tests = [("true;", true), ("false;", false)];
+
+        for (input, expected) in tests {
+            let program = parse(input);
+
+            match single_expression(&program) {
+                Expression::Boolean(boolean) => assert_eq!(boolean.value, expected),
+                other => panic!("stmt.expression is not ast::Boolean. got={:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsing_prefix_expressions() {
+        let tests = [
+            ("!5;", "!", "5"),
+            ("-15;", "-", "15"),
+            ("!true;", "!", "true"),
+            ("!false;", "!", "false"),
+            ("-a;", "-", "a"),
+        ];
+
+        for (input, expected_operator, expected_right) in tests {
+            let program = parse(input);
+
+            match single_expression(&program) {
+                Expression::Prefix(prefix) => {
+                    assert_eq!(prefix.operator, expected_operator);
+                    assert_eq!(prefix.right.to_string(), expected_right);
                 }
+                other => panic!(
+                    "stmt.expression is not ast::PrefixExpression. got={:?}",
+                    other
+                ),
             }
-            None => panic!(
-                "program.statements[0] is not ast::ExpressionStatement. got={:?}",
-                stmt_trait_object.token_literal()
+        }
+    }
+
+    #[test]
+    fn test_prefix_operator_precedence_parsing() {
+        let tests = [
+            ("-a * b", "((-a) * b)"),
+            ("!-a", "(!(-a))"),
+            ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
+            (
+                "3 + 4 * 5 == 3 * 1 + 4 * 5",
+                "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))",
+            ),
+            ("true", "true"),
+            ("3 > 5 == false", "((3 > 5) == false)"),
+            ("3 < 5 == true", "((3 < 5) == true)"),
+            ("-5 - -5", "((-5) - (-5))"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(parse(input).to_string(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_if_expression() {
+        let program = parse("if (x < y) { x }");
+
+        let if_expr = match single_expression(&program) {
+            Expression::If(if_expr) => if_expr,
+            other => panic!("stmt.expression is not ast::IfExpression. got={:?}", other),
+        };
+
+        assert_eq!(if_expr.condition.to_string(), "(x < y)");
+        assert_eq!(if_expr.consequence.statements.len(), 1);
+        assert_eq!(if_expr.consequence.statements[0].to_string(), "x");
+        assert!(if_expr.alternative.is_none());
+    }
+
+    #[test]
+    fn test_if_else_expression() {
+        let program = parse("if (x < y) { x } else { y }");
+
+        let if_expr = match single_expression(&program) {
+            Expression::If(if_expr) => if_expr,
+            other => panic!("stmt.expression is not ast::IfExpression. got={:?}", other),
+        };
+
+        let alternative = if_expr.alternative.as_ref().expect("alternative is None");
+        assert_eq!(alternative.statements.len(), 1);
+        assert_eq!(alternative.statements[0].to_string(), "y");
+    }
+
+    #[test]
+    fn test_if_expression_strings() {
+        let tests = [
+            ("if (a) {}", "if a {}"),
+            (
+                "if (a > b) { return a; } else { return b; }",
+                "if (a > b) { return a; } else { return b; }",
+            ),
+            (
+                "if (a) { if (b) { c } else { let d = 1; d } }",
+                "if a { if b { c } else { let d = 1;d } }",
+            ),
+            (
+                "let max = if (a > b) { a } else { b };",
+                "let max = if (a > b) { a } else { b };",
+            ),
+            ("if (a) { return }", "if a { return; }"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(parse(input).to_string(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_if_expression_missing_braces() {
+        let tests = [
+            ("if (a) b }", "expected LeftBrace, got Identifier instead"),
+            (
+                "if (a) { b",
+                "expected RightBrace to close block, got EOF instead",
+            ),
+            (
+                "if (a) { b } else c",
+                "expected LeftBrace, got Identifier instead",
+            ),
+            (
+                "if (a) { if (b) { c }",
+                "expected RightBrace to close block, got EOF instead",
+            ),
+            ("if a { b }", "expected LeftParen, got Identifier instead"),
+            ("if (a { b }", "expected RightParen, got LeftBrace instead"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(
+                first_error(input).as_deref(),
+                Some(expected),
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_function_literal_parsing() {
+        let program = parse("fn(x, y) { x + y; }");
+
+        let function = match single_expression(&program) {
+            Expression::FunctionLiteral(function) => function,
+            other => panic!(
+                "stmt.expression is not ast::FunctionLiteral. got={:?}",
+                other
+            ),
+        };
+
+        let parameters: Vec<&str> = function
+            .parameters
+            .iter()
+            .map(|parameter| parameter.value.as_str())
+            .collect();
+        assert_eq!(parameters, ["x", "y"]);
+        assert_eq!(function.body.statements.len(), 1);
+        assert_eq!(function.body.statements[0].to_string(), "(x + y)");
+    }
+
+    #[test]
+    fn test_function_parameter_parsing() {
+        let tests = [
+            ("fn() {};", vec![]),
+            ("fn(x) {};", vec!["x"]),
+            ("fn(x, y, z) {};", vec!["x", "y", "z"]),
+        ];
+
+        for (input, expected) in tests {
+            let program = parse(input);
+
+            let function = match single_expression(&program) {
+                Expression::FunctionLiteral(function) => function,
+                other => panic!(
+                    "stmt.expression is not ast::FunctionLiteral. got={:?}",
+                    other
+                ),
+            };
+            let parameters: Vec<&str> = function
+                .parameters
+                .iter()
+                .map(|parameter| parameter.value.as_str())
+                .collect();
+            assert_eq!(parameters, expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_array_literal_parsing() {
+        let program = parse("[1, 2 * 2, 3 + 3]");
+
+        let array = match single_expression(&program) {
+            Expression::ArrayLiteral(array) => array,
+            other => panic!("expression is not ast::ArrayLiteral. got={:?}", other),
+        };
+
+        let elements: Vec<String> = array.elements.iter().map(|e| e.to_string()).collect();
+        assert_eq!(elements, ["1", "(2 * 2)", "(3 + 3)"]);
+        assert_eq!(parse("[]").to_string(), "[]");
+    }
+
+    #[test]
+    fn test_index_expression_parsing() {
+        let program = parse("myArray[1 + 1]");
+
+        let index = match single_expression(&program) {
+            Expression::Index(index) => index,
+            other => panic!("expression is not ast::IndexExpression. got={:?}", other),
+        };
+
+        assert_eq!(index.left.to_string(), "myArray");
+        assert_eq!(index.index.to_string(), "(1 + 1)");
+        assert_eq!((index.span.start, index.span.end), (0, 14));
+    }
+
+    #[test]
+    fn test_hash_literal_parsing() {
+        let program = parse("{\"one\": 1, \"two\": 2 * 2, 3: true}");
+
+        let hash = match single_expression(&program) {
+            Expression::HashLiteral(hash) => hash,
+            other => panic!("expression is not ast::HashLiteral. got={:?}", other),
+        };
+
+        let pairs: Vec<(String, String)> = hash
+            .pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("\"one\"".to_string(), "1".to_string()),
+                ("\"two\"".to_string(), "(2 * 2)".to_string()),
+                ("3".to_string(), "true".to_string()),
+            ]
+        );
+
+        let tests = [
+            ("{}", "{}"),
+            ("{a + b: c}[a]", "({(a + b): c}[a])"),
+            // A '{' starting an expression is a hash, even inside a block
+            ("if (a) { {b: c} }", "if a { {b: c} }"),
+            ("fn() { {} }", "fn() { {} }"),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(parse(input).to_string(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_hash_literal_errors() {
+        let tests = [
+            ("{1 2}", "expected Colon, got Integer instead"),
+            ("{1: 2 3: 4}", "expected Comma, got Integer instead"),
+            ("{1: 2,}", "no prefix parse function for RightBrace found"),
+            (
+                "{1: 2",
+                "expected RightBrace to close block, got EOF instead",
+            ),
+            ("{", "expected RightBrace to close block, got EOF instead"),
+            (
+                "{1: 2, 3: 4",
+                "expected RightBrace to close block, got EOF instead",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(
+                first_error(input).as_deref(),
+                Some(expected),
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_unclosed_hash_literal_points_at_its_brace() {
+        let mut parser = Parser::new(Lexer::new("let h = {1: 2".as_bytes()));
+        parser.parse_program();
+        match parser.errors() {
+            [ParseError::UnterminatedBlock { open, found }] => {
+                assert_eq!(open.token_type, TokenType::LeftBrace);
+                assert_eq!(open.span.start, 8);
+                assert_eq!(found.token_type, TokenType::EOF);
+            }
+            other => panic!("expected one UnterminatedBlock error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_call_expression_parsing() {
+        let program = parse("add(1, 2 * 3, 4 + 5);");
+
+        let call = match single_expression(&program) {
+            Expression::Call(call) => call,
+            other => panic!(
+                "stmt.expression is not ast::CallExpression. got={:?}",
+                other
+            ),
+        };
+
+        assert_eq!(call.function.to_string(), "add");
+        let arguments: Vec<String> = call
+            .arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+        assert_eq!(arguments, ["1", "(2 * 3)", "(4 + 5)"]);
+    }
+
+    #[test]
+    fn test_call_and_grouping_precedence_parsing() {
+        let tests = [
+            ("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4)"),
+            ("(5 + 5) * 2", "((5 + 5) * 2)"),
+            ("-(5 + 5)", "(-(5 + 5))"),
+            ("!(true == true)", "(!(true == true))"),
+            ("a + add(b * c) + d", "((a + add((b * c))) + d)"),
+            (
+                "add(a, b, 1, 2 * 3, 4 + 5, add(6, 7 * 8))",
+                "add(a, b, 1, (2 * 3), (4 + 5), add(6, (7 * 8)))",
+            ),
+            (
+                "add(a + b + c * d / f + g)",
+                "add((((a + b) + ((c * d) / f)) + g))",
             ),
+            ("add(1, 2 * 3)(4)", "add(1, (2 * 3))(4)"),
+            ("fn(x) { x }(5)", "fn(x) { x }(5)"),
+            ("let id = fn(x) { x; };", "let id = fn(x) { x };"),
+            ("f()", "f()"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(parse(input).to_string(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_function_and_call_errors() {
+        let tests = [
+            ("fn(x, 1) { x }", "expected Identifier, got Integer instead"),
+            ("fn(x { x }", "expected RightParen, got LeftBrace instead"),
+            ("add(1, 2", "expected RightParen, got EOF instead"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(
+                first_error(input).as_deref(),
+                Some(expected),
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_ast_is_matchable_and_cloneable() {
+        let program = parse("let add = fn(a, b) { return a + b; }; add(1, 2);");
+        assert_eq!(program.clone(), program);
+
+        match &program.statements[..] {
+            [Statement::Let(LetStatement {
+                value: Expression::FunctionLiteral(function),
+                ..
+            }), Statement::Expression(ExpressionStatement {
+                expression: Expression::Call(call),
+                ..
+            })] => {
+                assert_eq!(function.parameters.len(), 2);
+                assert!(matches!(*call.function, Expression::Identifier(_)));
+                assert_eq!(call.arguments.len(), 2);
+            }
+            other => panic!("unexpected program shape: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_node_spans() {
+        let input = "let x = 1 + 2;\nadd(x, -3)\nif (x) { x } else { 0 }";
+        let program = parse(input);
+
+        assert_eq!(program.statements.len(), 3);
+        assert_eq!(program.span(), Span::new(0, 49, 1, 1));
+
+        let let_stmt = match &program.statements[0] {
+            Statement::Let(let_stmt) => let_stmt,
+            other => panic!("Statement is not a LetStatement. Got: {:?}", other),
+        };
+        assert_eq!(let_stmt.span, Span::new(0, 14, 1, 1));
+        assert_eq!(let_stmt.name.span, Span::new(4, 5, 1, 5));
+        assert_eq!(let_stmt.value.span(), Span::new(8, 13, 1, 9));
+
+        let call = match &program.statements[1] {
+            Statement::Expression(ExpressionStatement {
+                span,
+                expression: Expression::Call(call),
+                ..
+            }) => {
+                assert_eq!(*span, Span::new(15, 25, 2, 1));
+                call
+            }
+            other => panic!("Statement is not a call. Got: {:?}", other),
+        };
+        assert_eq!(call.span, Span::new(15, 25, 2, 1));
+        assert_eq!(call.function.span(), Span::new(15, 18, 2, 1));
+        assert_eq!(call.arguments[1].span(), Span::new(22, 24, 2, 8));
+
+        let if_expr = match &program.statements[2] {
+            Statement::Expression(ExpressionStatement {
+                expression: Expression::If(if_expr),
+                ..
+            }) => if_expr,
+            other => panic!("Statement is not an if. Got: {:?}", other),
+        };
+        assert_eq!(if_expr.span, Span::new(26, 49, 3, 1));
+        assert_eq!(if_expr.consequence.span, Span::new(33, 38, 3, 8));
+        assert_eq!(
+            if_expr.alternative.as_ref().unwrap().span,
+            Span::new(44, 49, 3, 19)
+        );
+    }
+
+    #[test]
+    fn test_parse_error_variants() {
+        let lexer = Lexer::new("let = 5;".as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        match &parser.errors()[0] {
+            ParseError::UnexpectedToken { expected, found } => {
+                assert_eq!(expected, &vec![TokenType::Identifier]);
+                assert_eq!(found.token_type, TokenType::Assign);
+                assert_eq!(found.span, Span::new(4, 5, 1, 5));
+            }
+            other => panic!("expected UnexpectedToken, got {:?}", other),
+        }
+        assert_eq!(
+            parser.errors()[0].to_string(),
+            "1:5: expected Identifier, got Assign instead"
+        );
+
+        let lexer = Lexer::new("x;\n  ;".as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        match parser.errors() {
+            [ParseError::NoPrefixParser { token }] => {
+                assert_eq!(token.token_type, TokenType::Semicolon);
+                assert_eq!(token.span.to_string(), "2:3");
+            }
+            other => panic!("expected a single NoPrefixParser error, got {:?}", other),
+        }
+
+        let lexer = Lexer::new("if (x) {\n x".as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        match &parser.errors()[0] {
+            error @ ParseError::UnterminatedBlock { open, found } => {
+                assert_eq!(open.span, Span::new(7, 8, 1, 8));
+                assert_eq!(found.token_type, TokenType::EOF);
+                assert_eq!(error.expected(), &[TokenType::RightBrace]);
+            }
+            other => panic!("expected UnterminatedBlock, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_try_parse_program() {
+        let lexer = Lexer::new("let x = 5; x".as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = parser.try_parse_program().expect("program should parse");
+        assert_eq!(program.to_string(), "let x = 5;x");
+
+        let lexer = Lexer::new("let x 5;".as_bytes());
+        let mut parser = Parser::new(lexer);
+        let errors = parser.try_parse_program().unwrap_err();
+        assert_eq!(
+            errors[0],
+            ParseError::UnexpectedToken {
+                expected: vec![TokenType::Assign],
+                found: Token::new(TokenType::Integer, "5".to_string())
+                    .with_span(Span::new(6, 7, 1, 7)),
+            }
+        );
+
+        // The errors are boxable like any other std error
+        let boxed: Box<dyn std::error::Error> = Box::new(errors[0].clone());
+        assert_eq!(
+            boxed.to_string(),
+            "1:7: expected Assign, got Integer instead"
+        );
+    }
+
+    fn parse_error_messages(input: &str) -> Vec<String> {
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        parser.errors().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_recovery_reports_each_mistake_once() {
+        let input = r#"let = 1;
+let x 2;
+let y = ;
+return );
+add(1, 2;
+let ok = 5;
+"#;
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        assert_eq!(
+            parser
+                .errors()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "1:5: expected Identifier, got Assign instead",
+                "2:7: expected Assign, got Integer instead",
+                "3:9: no prefix parse function for Semicolon found",
+                "4:8: no prefix parse function for RightParen found",
+                "5:9: expected RightParen, got Semicolon instead",
+            ]
+        );
+        assert_eq!(program.to_string(), "let ok = 5;");
+    }
+
+    #[test]
+    fn test_recovery_without_semicolons() {
+        // Statements that are not terminated by `;` resynchronize on the next `let` / `return`
+        assert_eq!(
+            parse_error_messages("let a 1 let b = 2 return ) let c = 3"),
+            [
+                "1:7: expected Assign, got Integer instead",
+                "1:26: no prefix parse function for RightParen found",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recovery_inside_blocks() {
+        let input = r#"let f = fn(x) {
+    let = x;
+    return x +;
+    x
+};
+let g = if (x { 1 } else { 2 };
+let h = fn() { let 1 };
+}
+let ok = 1;
+"#;
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        assert_eq!(
+            parser
+                .errors()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "2:9: expected Identifier, got Assign instead",
+                "3:15: no prefix parse function for Semicolon found",
+                "6:15: expected RightParen, got LeftBrace instead",
+                "7:20: expected Identifier, got Integer instead",
+                "8:1: no prefix parse function for RightBrace found",
+            ]
+        );
+        assert_eq!(
+            program.to_string(),
+            "let f = fn(x) { x };let h = fn() {};let ok = 1;"
+        );
+    }
+
+    #[test]
+    fn test_string_literal_expression() {
+        let program = parse(r#""hello\tworld";"#);
+
+        match single_expression(&program) {
+            Expression::StringLiteral(string) => {
+                assert_eq!(string.value, "hello\tworld");
+                assert_eq!(string.span, Span::new(0, 14, 1, 1));
+            }
+            other => panic!("stmt.expression is not ast::StringLiteral. got={:?}", other),
+        }
+
+        assert_eq!(
+            parse(r#"let greeting = "say \"hi\"\n"; f("a", "b")"#).to_string(),
+            r#"let greeting = "say \"hi\"\n";f("a", "b")"#
+        );
+    }
+
+    #[test]
+    fn test_lexical_errors_are_reported() {
+        let input = "let a = \"bad \\z\";\nlet b = \"open";
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        // The malformed strings still parse, so no syntax errors pile up behind them
+        assert_eq!(program.statements.len(), 2);
+        assert_eq!(
+            parser.errors(),
+            &[
+                ParseError::Lexical(LexError::InvalidEscape {
+                    sequence: "\\z".to_string(),
+                    span: Span::new(13, 15, 1, 14),
+                }),
+                ParseError::Lexical(LexError::UnterminatedString {
+                    span: Span::new(26, 31, 2, 9),
+                }),
+            ]
+        );
+        assert_eq!(
+            parser.errors()[1].to_string(),
+            "2:9: unterminated string literal"
+        );
+    }
+
+    #[test]
+    fn test_peek_nth_token() {
+        let lexer = Lexer::new("a /* skipped */ b // too\n c".as_bytes()).emit_comments(true);
+        let mut parser = Parser::new(lexer);
+
+        assert_eq!(parser.current_token.literal, "a");
+        assert_eq!(parser.peek_nth_token(0).literal, "b");
+        assert_eq!(parser.peek_nth_token(1).literal, "c");
+        assert_eq!(parser.peek_nth_token(2).token_type, TokenType::EOF);
+
+        // Looking ahead does not consume anything
+        parser.next_token();
+        assert_eq!(parser.current_token.literal, "b");
+        assert_eq!(parser.peek_token.literal, "c");
+    }
+
+    #[test]
+    fn test_localized_keywords() {
+        let keywords = KeywordTable::english().merge(&KeywordTable::persian());
+        let input = "بگذار بیشینه = تابع(a, b) { اگر (a > b) { a } وگرنه { b } };\n\
+                     let x = بیشینه(1, 2);\n\
+                     برگردان x;";
+        let lexer = Lexer::with_keywords(input.as_bytes(), &keywords);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&parser);
+
+        assert_eq!(program.statements.len(), 3);
+        match &program.statements[0] {
+            Statement::Let(statement) => {
+                assert_eq!(statement.name.value, "بیشینه");
+                assert!(matches!(statement.value, Expression::FunctionLiteral(_)));
+            }
+            other => panic!("expected a let statement, got {:?}", other),
         }
+        assert!(matches!(program.statements[1], Statement::Let(_)));
+        assert!(matches!(program.statements[2], Statement::Return(_)));
+    }
+
+    #[test]
+    fn test_lexical_errors_come_first() {
+        let input = "let = 5;\nlet b = 1 @ 2;";
+        let lexer = Lexer::new(input.as_bytes());
+        let mut parser = Parser::new(lexer);
+        parser.parse_program();
+
+        // The lexer reports `@` after the syntax error was found, but it is listed first
+        let messages: Vec<String> = parser.errors().iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "2:11: unexpected character `@`",
+                "1:5: expected Identifier, got Assign instead"
+            ]
+        );
+
+        assert_eq!(
+            parser.lexical_errors(),
+            &[ParseError::Lexical(LexError::UnexpectedCharacter {
+                ch: '@',
+                span: Span::new(19, 20, 2, 11),
+            })]
+        );
+        assert_eq!(parser.syntax_errors().len(), 1);
+        assert!(parser.errors()[0].is_lexical());
+        assert!(!parser.syntax_errors()[0].is_lexical());
+    }
+
+    #[test]
+    fn test_comments_are_ignored() {
+        let input = "let x = 1; // one\n/* two */ let y = x /* three */ + 2;";
+        assert_eq!(parse(input).to_string(), "let x = 1;let y = (x + 2);");
+
+        // Even when the lexer hands them out, comments never reach the grammar
+        let lexer = Lexer::new(input.as_bytes()).emit_comments(true);
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+        check_parser_errors(&parser);
+        assert_eq!(program.to_string(), "let x = 1;let y = (x + 2);");
     }
 }
@@ -1,10 +1,15 @@
+use ast::Statement;
+use evaluator::{Environment, eval};
 use lexer::Lexer;
-use lexer::TokenType;
+use parser::Parser;
+use parser::diagnostics::{Diagnostic, RenderMode, render_errors};
 use std::io::{BufRead, Write};
 
 const PROMPT: &str = ">> ";
 
-pub fn start<R: BufRead, W: Write>(mut input: R, mut output: W) {
+pub fn start<R: BufRead, W: Write>(mut input: R, mut output: W, mode: RenderMode) {
+    // Shared by every line, so bindings carry over from one line to the next
+    let mut env = Environment::new();
     loop {
         // Print prompt
         write!(output, "{}", PROMPT).unwrap();
@@ -18,16 +23,24 @@ pub fn start<R: BufRead, W: Write>(mut input: R, mut output: W) {
             break;
         }
 
-        // Create a lexer for the input line
-        let mut lexer = Lexer::new(line.as_bytes());
-
-        // Print tokens until EOF
-        loop {
-            let tok = lexer.next_token();
-            if tok.token_type == TokenType::EOF {
-                break;
+        // Run the line and print its value, or explain why it did not parse or run
+        let lexer = Lexer::new(line.as_bytes());
+        let mut parser = Parser::new(lexer);
+        let program = match parser.try_parse_program() {
+            Ok(program) => program,
+            Err(errors) => {
+                write!(output, "{}", render_errors(&errors, line.as_bytes(), mode)).unwrap();
+                continue;
+            }
+        };
+        match eval(&program, &mut env) {
+            // A line ending in `let` only binds a name; its null is not worth printing
+            Ok(_) if matches!(program.statements.last(), Some(Statement::Let(_))) => {}
+            Ok(value) => writeln!(output, "{}", value).unwrap(),
+            Err(error) => {
+                let diagnostic = Diagnostic::error(error.message(), error.span());
+                write!(output, "{}", diagnostic.render(line.as_bytes(), mode)).unwrap();
             }
-            writeln!(output, "{:?}", tok).unwrap();
         }
     }
 }
@@ -1,15 +1,37 @@
+use parser::diagnostics::RenderMode;
 use repl::start;
-use std::io::{self, BufReader, BufWriter};
+use std::io::{self, BufReader, BufWriter, IsTerminal};
+use std::thread;
 
 fn main() {
+    // Deep recursion in the evaluated program needs more stack than the main
+    // thread has
+    let repl = thread::Builder::new()
+        .name("repl".to_string())
+        .stack_size(evaluator::STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the REPL thread");
+    if let Err(panic) = repl.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+fn run() {
     println!("Hello! This is the Mazgamet programming language!");
     println!("Feel free to type in commands");
 
     let stdin = io::stdin();
     let stdout = io::stdout();
 
+    // Only color diagnostics when a person is looking at them
+    let mode = if stdout.is_terminal() {
+        RenderMode::Ansi
+    } else {
+        RenderMode::Plain
+    };
+
     let reader = BufReader::new(stdin.lock());
     let writer = BufWriter::new(stdout.lock());
 
-    start(reader, writer);
+    start(reader, writer, mode);
 }